@@ -1,47 +1,72 @@
 use super::{
     about::{self, About},
-    config::Config,
+    bookmark::{self, Bookmarks},
+    config::{self, Config, ConfigConflict, ConfigReadOnly, Density, Dirs},
+    digest::{self, DigestPanel},
+    export::{self, ExportPanel},
+    font,
+    list::VirtualList,
     news,
-    news::NewsItem,
-    theme,
-    tr::tr,
+    news::{Language, NewsItem},
+    read_state::ReadState,
+    settings::{self, SettingsPanel},
+    store, theme,
+    tr::{self, tr, tr_args},
+    translate::{self, Translated, Translations},
     util,
+    zh::{self, ChineseScript},
 };
+use chrono::{Local, Timelike};
 use egui::{
-    containers::scroll_area::ScrollBarVisibility, containers::Frame, Align, Button, Color32,
-    Context, FontId, ImageButton, Layout, Pos2, RichText, ScrollArea, Stroke, TextureHandle, Ui,
-    Window,
+    containers::scroll_area::ScrollBarVisibility,
+    containers::Frame,
+    epaint::text::{LayoutJob, TextFormat, TextWrapping},
+    Align, Button, Color32, ComboBox, Context, FontId, Key, Layout, Modifiers, Pos2, RichText,
+    ScrollArea, Slider, Stroke, TextureHandle, Ui, Vec2, Window,
 };
+use fluent_bundle::FluentArgs;
 use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{hash_map::DefaultHasher, HashSet};
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use std::sync::mpsc::{self, Receiver, SyncSender};
 use std::sync::Arc;
+use std::time::Duration;
+
+const FONT_SCALE_STEP: f32 = 0.1;
+pub const MIN_FONT_SCALE: f32 = 0.5;
+pub const MAX_FONT_SCALE: f32 = 3.0;
+
+// How long a popup message is shown
+const MSG_SECS: i64 = 5;
 
 #[allow(unused)]
-#[derive(Clone, Debug)]
-enum MsgType {
+#[derive(Clone, Debug, Default)]
+pub enum MsgType {
+    #[default]
     Info,
     Warn,
     Success,
     Danger,
 }
 
-impl Default for MsgType {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum CurrentPanel {
+    #[default]
     News,
+    Bookmarks,
+    Digest,
+    Settings,
     About,
 }
 
-impl Default for CurrentPanel {
-    fn default() -> Self {
-        Self::News
-    }
+#[derive(Clone, Debug)]
+pub enum ItemAction {
+    ToggleBookmark,
+    ToggleSelect,
+    OpenLink,
+    Translate,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -51,14 +76,43 @@ struct MsgSpec {
     timestamp: i64,
 }
 
-#[derive(Clone, Debug)]
+// The icons decoded in the background
+#[derive(Clone)]
+struct Icons {
+    brand: TextureHandle,
+    refresh: TextureHandle,
+    language: TextureHandle,
+    about: TextureHandle,
+    back: TextureHandle,
+    reset: TextureHandle,
+}
+
+#[derive(Clone)]
 enum ChannelItem {
     ErrMsg(String),
-    NewsItems((bool, Vec<NewsItem>)),
+    IconsLoaded(Box<Icons>),
+    StateLoaded(Box<(Bookmarks, ReadState, Translations)>),
+    CacheLoaded((Language, Vec<NewsItem>)),
+    LoadDone,
+    NewsItems((Language, Vec<NewsItem>)),
+    FetchDone,
+    ConfigChanged,
+    Translated((String, NewsItem, Translated)),
+    TranslateFailed((String, String)),
+}
+
+// The merged and filtered timeline of the shown feeds as the indices into the feeds, which is
+// rebuilt once the feeds, the shown languages or the muted keywords change
+#[derive(Clone, Debug, Default)]
+struct Timeline {
+    languages: Vec<Language>,
+    muted_keywords: Vec<String>,
+    rows: Option<Vec<(Language, usize)>>,
 }
 
 #[derive(Clone)]
 pub struct App {
+    pub is_loading: bool,
     pub is_fetching: bool,
     pub is_scroll_to_top: bool,
     pub news_items_cn: Vec<NewsItem>,
@@ -67,17 +121,40 @@ pub struct App {
     pub current_panel: CurrentPanel,
     pub conf: Config,
 
+    // the loaded fonts, which are behind the config while the font list is typed in the settings
+    font: Option<config::Font>,
+
+    pub bookmarks: Bookmarks,
+    pub read_state: ReadState,
+    pub translations: Translations,
+    translating: HashSet<String>,
+    is_zooming: bool,
+    fetched_at: i64,
+
+    // the day of the last scheduled digest, which is generated once per day
+    digest_date: String,
+
+    #[allow(unused)]
     pub about_panel: About,
+    pub export_panel: ExportPanel,
+    pub digest_panel: DigestPanel,
+    pub settings_panel: SettingsPanel,
     msg_spec: MsgSpec,
+    news_rows: VirtualList,
+    timeline: RefCell<Timeline>,
 
     tx: Arc<SyncSender<ChannelItem>>,
     rx: Rc<RefCell<Receiver<ChannelItem>>>,
 
+    // the frames are painted on demand, so the background jobs wake the UI up with it
+    ctx: Context,
+
     brand_icon: Option<TextureHandle>,
     refresh_icon: Option<TextureHandle>,
     language_icon: Option<TextureHandle>,
     about_icon: Option<TextureHandle>,
     pub back_icon: Option<TextureHandle>,
+    pub reset_icon: Option<TextureHandle>,
 }
 
 impl Default for App {
@@ -85,6 +162,7 @@ impl Default for App {
         let (tx, rx) = mpsc::sync_channel(10);
 
         Self {
+            is_loading: false,
             is_fetching: false,
             is_scroll_to_top: false,
             news_items_cn: vec![],
@@ -92,9 +170,23 @@ impl Default for App {
 
             current_panel: Default::default(),
             msg_spec: Default::default(),
+            news_rows: Default::default(),
+            timeline: Default::default(),
+            ctx: Default::default(),
             conf: Default::default(),
+            font: Default::default(),
+            bookmarks: Default::default(),
+            read_state: Default::default(),
+            translations: Default::default(),
+            translating: Default::default(),
+            is_zooming: false,
+            fetched_at: 0,
+            digest_date: String::default(),
 
             about_panel: Default::default(),
+            export_panel: Default::default(),
+            digest_panel: Default::default(),
+            settings_panel: Default::default(),
 
             tx: Arc::new(tx),
             rx: Rc::new(RefCell::new(rx)),
@@ -104,64 +196,136 @@ impl Default for App {
             language_icon: None,
             back_icon: None,
             about_icon: None,
+            reset_icon: None,
         }
     }
 }
 
 impl App {
-    pub fn init(&mut self, ctx: &Context) {
-        if let Err(e) = self.conf.init() {
+    pub fn init(&mut self, ctx: &Context, dirs: &Dirs) {
+        self.ctx = ctx.clone();
+
+        if let Err(e) = self.conf.init(dirs) {
             log::warn!("{e:?}");
         }
+        tr::set_locale(&self.conf.ui.locale);
 
-        (self.news_items_cn, self.news_items_en) = news::load(self.conf.cache_dir.as_path());
+        // the problems of the config file are translated once the locale is set
+        if !self.conf.warnings.is_empty() {
+            let msg = self
+                .conf
+                .warnings
+                .iter()
+                .map(|w| w.message())
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.show_message(msg, MsgType::Warn);
+        }
 
-        self.fetch_data();
+        theme::set_theme(ctx, theme::load(&self.conf));
+        self.apply_font(ctx);
 
-        self.brand_icon = Some(ctx.load_texture(
-            "brand-icon",
-            theme::load_image_from_memory(theme::BRAND_ICON),
-            Default::default(),
-        ));
+        self.load_data(ctx);
 
-        self.refresh_icon = Some(ctx.load_texture(
-            "refresh-icon",
-            theme::load_image_from_memory(theme::REFRESH_ICON),
-            Default::default(),
-        ));
+        let tx = self.tx.clone();
+        let watch_ctx = ctx.clone();
+        config::watch(self.conf.config_path.clone(), move || {
+            // never dropped, as the watcher does not report the same change again
+            let _ = tx.send(ChannelItem::ConfigChanged);
+            watch_ctx.request_repaint();
+        });
+    }
 
-        self.language_icon = Some(ctx.load_texture(
-            "language-icon",
-            theme::load_image_from_memory(theme::LANGUAGE_ICON),
-            Default::default(),
-        ));
+    // Decode the icons and read the data files in the background, so that the first frame is
+    // shown at once. The shown feeds are loaded first, and the news is fetched once all is loaded
+    fn load_data(&mut self, ctx: &Context) {
+        self.is_loading = true;
 
-        self.about_icon = Some(ctx.load_texture(
-            "about-icon",
-            theme::load_image_from_memory(theme::ABOUT_ICON),
-            Default::default(),
-        ));
+        let tx = self.tx.clone();
+        let ctx = ctx.clone();
+        let conf = self.conf.clone();
+        let shown = self.shown_languages();
+        let rest = Language::ALL.into_iter().filter(|l| !shown.contains(l));
+        let languages = shown.clone().into_iter().chain(rest).collect::<Vec<_>>();
 
-        self.back_icon = Some(ctx.load_texture(
-            "back-icon",
-            theme::load_image_from_memory(theme::BACK_ICON),
-            Default::default(),
-        ));
+        std::thread::spawn(move || {
+            let load_icon = |name: &str, data: &[u8]| {
+                ctx.load_texture(
+                    name,
+                    theme::load_image_from_memory(data),
+                    Default::default(),
+                )
+            };
+
+            let icons = Icons {
+                brand: load_icon("brand-icon", theme::BRAND_ICON),
+                refresh: load_icon("refresh-icon", theme::REFRESH_ICON),
+                language: load_icon("language-icon", theme::LANGUAGE_ICON),
+                about: load_icon("about-icon", theme::ABOUT_ICON),
+                back: load_icon("back-icon", theme::BACK_ICON),
+                reset: load_icon("reset-icon", theme::RESET_ICON),
+            };
+            let _ = tx.send(ChannelItem::IconsLoaded(Box::new(icons)));
+            ctx.request_repaint();
+
+            let state = (
+                Bookmarks::load(conf.data_dir.join("bookmarks.json").as_path()),
+                ReadState::load(conf.data_dir.join("read.json").as_path()),
+                Translations::load(conf.data_dir.join("translations.json").as_path()),
+            );
+            let _ = tx.send(ChannelItem::StateLoaded(Box::new(state)));
+
+            for language in languages {
+                let items = news::load_items(&news::cache_path(&conf.cache_dir, language));
+                let _ = tx.send(ChannelItem::CacheLoaded((language, items)));
+                ctx.request_repaint();
+            }
+
+            let _ = tx.send(ChannelItem::LoadDone);
+            ctx.request_repaint();
+        });
+    }
+
+    // Save the edits not saved yet, e.g. before the app exits or goes to the background
+    pub fn flush(&mut self) {
+        self.bookmarks.flush();
+        if self.settings_panel.saves.flush() {
+            self.save_conf();
+        }
     }
 
     pub fn ui(&mut self, ctx: &Context) {
+        // the edits are saved once their panel is left
+        if self.current_panel != CurrentPanel::Bookmarks {
+            self.bookmarks.flush();
+        }
+        if self.current_panel != CurrentPanel::Settings {
+            if self.settings_panel.saves.flush() {
+                self.save_conf();
+            }
+            self.apply_font(ctx);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             match self.current_panel {
                 CurrentPanel::News => {
                     self.header(ui);
                     self.news_list(ui);
                 }
+                CurrentPanel::Bookmarks => bookmark::ui(self, ui),
+                CurrentPanel::Digest => digest::ui(self, ui),
+                CurrentPanel::Settings => settings::ui(self, ui),
                 CurrentPanel::About => about::ui(self, ui),
             }
 
-            self.update_data();
+            self.update_data(ui.ctx());
         });
 
+        self.handle_zoom(ctx);
+        self.auto_refresh(ctx);
+        self.scheduled_digest(ctx);
+
+        export::ui(self, ctx);
         self.popup_message(ctx);
     }
 
@@ -171,10 +335,24 @@ impl App {
 
         ui.horizontal(|ui| {
             ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-                ui.image(&self.brand_icon.clone().unwrap(), theme::ICON_SIZE);
-                ui.heading(
-                    RichText::new(tr(self.conf.ui.is_cn, "加密新闻")).color(theme::BRAND_COLOR),
-                );
+                if let Some(icon) = &self.brand_icon {
+                    ui.image(icon, theme::ICON_SIZE);
+                }
+                ui.heading(RichText::new(tr("app-name")).color(theme::current().brand_color));
+
+                let unread_count = self
+                    .read_state
+                    .unread_count(self.current_news_items().into_iter().map(|(_, item)| item));
+
+                if unread_count > 0 {
+                    ui.label(
+                        RichText::new(tr_args(
+                            "unread-count",
+                            Some(&FluentArgs::from_iter([("count", unread_count)])),
+                        ))
+                        .color(theme::current().light_color),
+                    );
+                }
             });
 
             // double-clicked-area to scroll to top
@@ -191,80 +369,429 @@ impl App {
             );
 
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.add_space(theme::PADDING * 2.);
+                ui.add_space(theme::current().padding * 2.);
+
+                if theme::image_button(ui, &self.about_icon, theme::SMALL_ICON_SIZE, "ℹ").clicked()
+                {
+                    self.current_panel = CurrentPanel::About;
+                }
 
                 if ui
                     .add(
-                        ImageButton::new(
-                            self.about_icon.clone().unwrap().id(),
-                            theme::SMALL_ICON_SIZE,
+                        Button::new(
+                            RichText::new("⚙")
+                                .color(theme::current().brand_color)
+                                .font(FontId::proportional(theme::SMALL_ICON_SIZE.y)),
                         )
                         .frame(false),
                     )
+                    .on_hover_text(tr("settings"))
                     .clicked()
                 {
-                    self.current_panel = CurrentPanel::About;
+                    settings::open(self);
                 }
 
                 if ui
                     .add(
-                        ImageButton::new(
-                            self.language_icon.clone().unwrap().id(),
-                            theme::ICON_SIZE,
+                        Button::new(
+                            RichText::new("★")
+                                .color(theme::current().brand_color)
+                                .font(FontId::proportional(theme::ICON_SIZE.y)),
                         )
                         .frame(false),
                     )
+                    .on_hover_text(tr("bookmarks"))
                     .clicked()
                 {
-                    self.conf.ui.is_cn = !self.conf.ui.is_cn;
-                    if let Err(e) = self.conf.save() {
-                        log::warn!("{e:?}");
-                    }
+                    self.current_panel = CurrentPanel::Bookmarks;
+                }
 
-                    // fetch data only without news cache
-                    if (self.conf.ui.is_cn && self.news_items_cn.is_empty())
-                        || (!self.conf.ui.is_cn && self.news_items_en.is_empty())
-                    {
-                        self.fetch_data();
-                    }
+                if ui
+                    .add(
+                        Button::new(
+                            RichText::new("📤")
+                                .color(theme::current().brand_color)
+                                .font(FontId::proportional(theme::SMALL_ICON_SIZE.y)),
+                        )
+                        .frame(false),
+                    )
+                    .on_hover_text(tr("export"))
+                    .clicked()
+                {
+                    export::open(self);
                 }
 
                 if ui
                     .add(
-                        ImageButton::new(self.refresh_icon.clone().unwrap().id(), theme::ICON_SIZE)
-                            .frame(false),
+                        Button::new(
+                            RichText::new("📰")
+                                .color(theme::current().brand_color)
+                                .font(FontId::proportional(theme::SMALL_ICON_SIZE.y)),
+                        )
+                        .frame(false),
                     )
+                    .on_hover_text(tr("daily-digest"))
                     .clicked()
                 {
+                    digest::open(self);
+                }
+
+                ui.menu_button(
+                    RichText::new("🎨")
+                        .color(theme::current().brand_color)
+                        .font(FontId::proportional(theme::SMALL_ICON_SIZE.y)),
+                    |ui| self.theme_menu(ui),
+                )
+                .response
+                .on_hover_text(tr("theme"));
+
+                match self.language_icon.clone() {
+                    Some(icon) => {
+                        ui.menu_image_button(icon.id(), theme::ICON_SIZE, |ui| {
+                            self.language_menu(ui)
+                        });
+                    }
+                    None => {
+                        ui.menu_button("🌐", |ui| self.language_menu(ui));
+                    }
+                }
+
+                if theme::image_button(ui, &self.refresh_icon, theme::ICON_SIZE, "⟳").clicked() {
                     self.fetch_data();
                 }
 
                 if self.is_fetching {
                     ui.label(
-                        RichText::new(tr(self.conf.ui.is_cn, "正在刷新"))
-                            .color(theme::NEWS_TITLE_COLOR),
+                        RichText::new(tr("refreshing")).color(theme::current().news_title_color),
                     );
                 }
             });
         });
 
-        ui.add_space(theme::SPACING);
+        ui.add_space(theme::current().spacing);
     }
 
-    fn news_list(&mut self, ui: &mut Ui) {
-        let row_height = ui.spacing().interact_size.y;
+    fn language_menu(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new(tr("ui-language")).color(theme::current().light_color));
+        for locale in tr::locales() {
+            if ui
+                .radio(self.conf.ui.locale == locale, tr::locale_name(locale))
+                .clicked()
+            {
+                self.conf.ui.locale = locale.to_string();
+                tr::set_locale(locale);
+                self.save_conf();
+                ui.close_menu();
+            }
+        }
 
-        let num_rows = if self.conf.ui.is_cn {
-            self.news_items_cn.len()
-        } else {
-            self.news_items_en.len()
-        };
+        ui.separator();
+
+        ui.label(RichText::new(tr("chinese-script")).color(theme::current().light_color));
+        for script in ChineseScript::ALL {
+            if ui
+                .radio(self.conf.ui.chinese_script == script, script.name())
+                .clicked()
+            {
+                self.conf.ui.chinese_script = script;
+                self.save_conf();
+                ui.close_menu();
+            }
+        }
 
-        let news_items = if self.conf.ui.is_cn {
-            &self.news_items_cn
+        ui.separator();
+
+        ui.label(RichText::new(tr("news-language")).color(theme::current().light_color));
+        for language in Language::ALL {
+            let text = tr(&format!("news-language-{}", language.code()));
+            let is_checked = !self.conf.ui.timeline && self.conf.ui.language == language;
+            if ui.radio(is_checked, text).clicked() {
+                self.conf.ui.language = language;
+                self.conf.ui.timeline = false;
+                self.save_conf();
+                ui.close_menu();
+                self.on_feed_changed();
+            }
+        }
+
+        if ui
+            .radio(self.conf.ui.timeline, tr("news-language-all"))
+            .clicked()
+        {
+            self.conf.ui.timeline = true;
+            self.save_conf();
+            ui.close_menu();
+            self.on_feed_changed();
+        }
+    }
+
+    fn on_feed_changed(&mut self) {
+        self.is_scroll_to_top = true;
+
+        // fetch data only without news cache
+        if self
+            .shown_languages()
+            .into_iter()
+            .any(|language| self.feed(language).is_empty())
+        {
+            self.fetch_data();
+        }
+    }
+
+    // The system theme may be changed while the app is running
+    pub fn on_system_theme_changed(&self, ctx: &Context) {
+        if self.conf.ui.theme == theme::SYSTEM_THEME {
+            theme::set_theme(ctx, theme::load(&self.conf));
+        }
+    }
+
+    fn theme_menu(&mut self, ui: &mut Ui) {
+        for (name, text) in theme::choices(&self.conf) {
+            if ui.radio(self.conf.ui.theme == name, text).clicked() {
+                self.conf.ui.theme = name;
+                theme::set_theme(ui.ctx(), theme::load(&self.conf));
+                self.save_conf();
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+        self.reading_menu(ui);
+    }
+
+    fn reading_menu(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new(tr("reading")).color(theme::current().light_color));
+
+        for density in Density::ALL {
+            if ui
+                .radio(self.conf.reading.density == density, tr(density.tr_id()))
+                .clicked()
+            {
+                self.conf.reading.density = density;
+                self.save_conf();
+            }
+        }
+
+        let reading = &mut self.conf.reading;
+        let responses = [
+            ui.add(
+                Slider::new(&mut reading.font_scale, MIN_FONT_SCALE..=MAX_FONT_SCALE)
+                    .text(tr("font-scale")),
+            ),
+            ui.add(Slider::new(&mut reading.line_spacing, 1.0..=2.0).text(tr("line-spacing"))),
+        ];
+
+        ui.separator();
+        ui.label(RichText::new(tr("font")).color(theme::current().light_color));
+
+        let mut is_font_changed = false;
+        for (id, font) in [
+            ("font-proportional", &mut self.conf.font.proportional),
+            ("font-monospace", &mut self.conf.font.monospace),
+        ] {
+            let selected = if font.is_empty() {
+                tr("font-default")
+            } else {
+                font.clone()
+            };
+
+            ComboBox::from_label(tr(id))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    is_font_changed |= ui
+                        .selectable_value(font, String::default(), tr("font-default"))
+                        .changed();
+
+                    for (name, _) in font::system_fonts().iter() {
+                        is_font_changed |= ui.selectable_value(font, name.clone(), name).changed();
+                    }
+                });
+        }
+
+        if is_font_changed {
+            self.apply_font(ui.ctx());
+            self.save_conf();
+        }
+
+        // save once the dragging is over
+        if responses
+            .iter()
+            .any(|r| r.drag_released() || (r.changed() && !r.dragged()))
+        {
+            self.save_conf();
+        }
+    }
+
+    // Apply the config edited in the settings panel, only the changed settings are reloaded. The
+    // fonts are left to `apply_font`, as reading them on every keystroke is slow
+    pub fn apply_conf(&mut self, ctx: &Context, conf: Config) {
+        let old = std::mem::replace(&mut self.conf, conf);
+
+        if old.ui.locale != self.conf.ui.locale {
+            tr::set_locale(&self.conf.ui.locale);
+        }
+
+        if old.ui.theme != self.conf.ui.theme {
+            theme::set_theme(ctx, theme::load(&self.conf));
+        }
+
+        if old.ui.language != self.conf.ui.language || old.ui.timeline != self.conf.ui.timeline {
+            self.on_feed_changed();
+        }
+    }
+
+    // Load the fonts if they are changed in the config
+    pub fn apply_font(&mut self, ctx: &Context) {
+        if self.font.as_ref() != Some(&self.conf.font) {
+            font::load(ctx, &self.conf.font, &self.conf.cache_dir);
+            self.font = Some(self.conf.font.clone());
+        }
+    }
+
+    pub fn save_conf(&mut self) {
+        match self.conf.save() {
+            // the external changes win
+            Err(e) if e.is::<ConfigConflict>() => {
+                let ctx = self.ctx.clone();
+                if self.reload_conf(&ctx) {
+                    self.show_message(tr("config-conflict"), MsgType::Warn);
+                }
+            }
+            Err(e) if e.is::<ConfigReadOnly>() => {
+                self.show_message(tr("config-read-only"), MsgType::Warn);
+            }
+            Err(e) => log::warn!("{e:?}"),
+            _ => (),
+        }
+    }
+
+    // Apply the changes of the config file made by another program, e.g. a dotfiles manager.
+    // True if the file is reloaded
+    fn reload_conf(&mut self, ctx: &Context) -> bool {
+        if !self.conf.is_changed_on_disk() {
+            return false;
+        }
+
+        match self.conf.reload() {
+            Ok(conf) => {
+                let msg = [tr("config-reloaded")]
+                    .into_iter()
+                    .chain(conf.warnings.iter().map(|w| w.message()))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.apply_conf(ctx, conf);
+                self.apply_font(ctx);
+                self.show_message(msg, MsgType::Info);
+                true
+            }
+            Err(e) => {
+                log::warn!("{e:?}");
+                self.show_message(tr("config-reload-failed"), MsgType::Warn);
+                false
+            }
+        }
+    }
+
+    pub fn feed(&self, language: Language) -> &Vec<NewsItem> {
+        match language {
+            Language::Cn => &self.news_items_cn,
+            Language::En => &self.news_items_en,
+        }
+    }
+
+    // The languages of the news list, all of them in the timeline
+    fn shown_languages(&self) -> Vec<Language> {
+        if self.conf.ui.timeline {
+            Language::ALL.to_vec()
         } else {
-            &self.news_items_en
-        };
+            vec![self.conf.ui.language]
+        }
+    }
+
+    fn timeline_rows(&self) -> Vec<(Language, usize)> {
+        let languages = self.shown_languages();
+        let muted_keywords = &self.conf.feed.muted_keywords;
+        let mut timeline = self.timeline.borrow_mut();
+
+        if timeline.rows.is_none()
+            || timeline.languages != languages
+            || &timeline.muted_keywords != muted_keywords
+        {
+            let mut rows = languages
+                .iter()
+                .flat_map(|&language| (0..self.feed(language).len()).map(move |i| (language, i)))
+                .filter(|&(language, i)| !news::is_muted(&self.feed(language)[i], muted_keywords))
+                .collect::<Vec<_>>();
+
+            // the same order as `news::timeline`
+            rows.sort_by_cached_key(|&(language, i)| {
+                Reverse(self.feed(language)[i].published_at())
+            });
+
+            *timeline = Timeline {
+                languages,
+                muted_keywords: muted_keywords.clone(),
+                rows: Some(rows),
+            };
+        }
+
+        timeline.rows.clone().unwrap_or_default()
+    }
+
+    pub fn current_news_items(&self) -> Vec<(Language, &NewsItem)> {
+        self.timeline_rows()
+            .into_iter()
+            .map(|(language, i)| (language, &self.feed(language)[i]))
+            .collect()
+    }
+
+    // The settings the heights of the news items depend on
+    fn news_style(&self) -> u64 {
+        let reading = &self.conf.reading;
+        let translation = &self.conf.translation;
+
+        let mut hasher = DefaultHasher::new();
+        (
+            reading.font_scale.to_bits(),
+            reading.line_spacing.to_bits(),
+            reading.density,
+            &self.conf.ui.theme,
+            self.conf.ui.chinese_script,
+            translation.enabled,
+            &translation.target,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn news_list(&mut self, ui: &mut Ui) {
+        let languages = self.shown_languages();
+
+        // borrow the fields rather than `self`, which is still mutated below
+        let (news_items_cn, news_items_en) = (&self.news_items_cn, &self.news_items_en);
+        let news_items = self
+            .timeline_rows()
+            .into_iter()
+            .map(|(language, i)| match language {
+                Language::Cn => (language, &news_items_cn[i]),
+                Language::En => (language, &news_items_en[i]),
+            })
+            .collect::<Vec<_>>();
+        let num_rows = news_items.len();
+
+        // the cache is still being read
+        if self.is_loading && num_rows == 0 {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr("loading"));
+            });
+            return;
+        }
+
+        // items above this row are where the previous session left off
+        let marker_row = news_items
+            .iter()
+            .position(|(language, item)| item.key() == self.read_state.marker(*language));
 
         let mut sarea = ScrollArea::vertical()
             .auto_shrink([false, false])
@@ -275,67 +802,412 @@ impl App {
             self.is_scroll_to_top = false;
         }
 
-        sarea.show_rows(ui, row_height, num_rows, |ui, row_range| {
-            for row in row_range {
-                self.show_news_item(ui, &news_items[row]);
+        let mut actions = vec![];
+        let keys = news_items
+            .iter()
+            .map(|(_, item)| item.key())
+            .collect::<Vec<_>>();
+        let style = self.news_style();
+
+        // the list is taken out of `self`, which is borrowed by the rows
+        let mut news_rows = std::mem::take(&mut self.news_rows);
+        let shown_rows = news_rows.show(ui, sarea, &keys, style, |ui, row| {
+            if row > 0 && marker_row == Some(row) {
+                self.last_visit_divider(ui);
+            }
+
+            let (language, item) = news_items[row];
+            if let Some(action) = self.show_news_item(ui, item, Some(language)) {
+                actions.push((row, action));
             }
         });
+        self.news_rows = news_rows;
+        let scrolled_past_rows = shown_rows.start;
+
+        let mut is_read_changed = self.read_state.mark_read(
+            news_items[..scrolled_past_rows]
+                .iter()
+                .map(|(_, item)| *item),
+        );
+
+        let mut translate_items = vec![];
+        for (row, action) in actions.into_iter() {
+            let (language, item) = news_items[row];
+            match action {
+                ItemAction::ToggleBookmark => self.bookmarks.toggle(item),
+                ItemAction::ToggleSelect => self.export_panel.toggle_selected(item),
+                ItemAction::OpenLink => {
+                    is_read_changed |= self.read_state.mark_read([item]);
+                }
+                ItemAction::Translate => translate_items.push((language, item.clone())),
+            }
+        }
+
+        for language in languages {
+            let items = match language {
+                Language::Cn => &self.news_items_cn,
+                Language::En => &self.news_items_en,
+            };
+            is_read_changed |= self.read_state.set_last_seen(language, items);
+        }
+
+        if is_read_changed {
+            if let Err(e) = self.read_state.save() {
+                log::warn!("{e:?}");
+            }
+        }
+
+        for (language, item) in translate_items.into_iter() {
+            self.translate(Some(language), item);
+        }
     }
 
-    fn show_news_item(&self, ui: &mut Ui, item: &NewsItem) {
+    pub fn translate(&mut self, language: Option<Language>, item: NewsItem) {
+        if !self.translating.insert(item.key().to_string()) {
+            return;
+        }
+
+        let tx = self.tx.clone();
+        let ctx = self.ctx.clone();
+        let conf = self.conf.translation.clone();
+        let network = self.conf.network.clone();
+
+        // the result is never dropped, or the item would be kept as being translated
+        std::thread::spawn(move || {
+            match translate::translate(&conf, &network, language, &item) {
+                Ok(translated) => {
+                    let _ = tx.send(ChannelItem::Translated((conf.target, item, translated)));
+                }
+                Err(e) => {
+                    let _ = tx.send(ChannelItem::TranslateFailed((
+                        item.key().to_string(),
+                        e.to_string(),
+                    )));
+                }
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn last_visit_divider(&self, ui: &mut Ui) {
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.label(RichText::new(tr("last-visit-divider")).color(theme::current().light_color));
+        });
+        ui.separator();
+        ui.add_space(theme::current().spacing);
+    }
+
+    // The language of the news is unknown in the bookmarks, the badge telling the language and
+    // the source is only shown in the timeline
+    pub fn show_news_item(
+        &self,
+        ui: &mut Ui,
+        item: &NewsItem,
+        language: Option<Language>,
+    ) -> Option<ItemAction> {
+        let mut action = None;
+        let badge = language.filter(|_| self.conf.ui.timeline);
+
+        let translation = &self.conf.translation;
+        let is_translatable = translation.enabled && translate::is_foreign(translation, language);
+        let translated = if is_translatable {
+            self.translations.get(&translation.target, item)
+        } else {
+            None
+        };
+
+        let is_read = self.read_state.is_read(item);
+        let script = self.conf.ui.chinese_script;
+        let theme = theme::current();
+        let reading = &self.conf.reading;
+
+        let (spacing, summary_rows) = match reading.density {
+            Density::Comfortable => (theme.spacing, Some(0)),
+            Density::Compact => (theme.spacing / 2., Some(2)),
+            Density::HeadlinesOnly => (theme.spacing / 2., None),
+        };
+
+        let title_font = FontId::proportional(theme.news_title_font_size * reading.font_scale);
+        let summary_font = FontId::proportional(theme.news_summary_font_size * reading.font_scale);
+
         ui.vertical(|ui| {
-            ui.label(
-                RichText::new(&item.title)
-                    .color(theme::NEWS_TITLE_COLOR)
-                    .font(FontId::proportional(theme::NEWS_TITLE_FONT_SIZE)),
+            let title_color = if is_read {
+                theme.read_news_title_color
+            } else {
+                theme.news_title_color
+            };
+            news_label(
+                ui,
+                &zh::convert(&item.title, script),
+                text_format(title_font.clone(), title_color, false),
+                0,
+                reading.line_spacing,
             );
 
-            ui.add_space(theme::SPACING);
+            if let Some(max_rows) = summary_rows {
+                ui.add_space(spacing);
 
-            ui.label(
-                RichText::new(&item.summary)
-                    .font(FontId::proportional(theme::NEWS_SUMMARY_FONT_SIZE)),
-            );
+                let summary_color = if is_read {
+                    theme.light_color
+                } else {
+                    ui.visuals().text_color()
+                };
+                news_label(
+                    ui,
+                    &zh::convert(&item.summary, script),
+                    text_format(summary_font.clone(), summary_color, false),
+                    max_rows,
+                    reading.line_spacing,
+                );
+            }
 
-            ui.add_space(theme::SPACING);
+            if let Some(translated) = translated {
+                ui.add_space(spacing);
+                news_label(
+                    ui,
+                    &translated.title,
+                    text_format(title_font, theme.read_news_title_color, true),
+                    0,
+                    reading.line_spacing,
+                );
+
+                if let Some(max_rows) = summary_rows {
+                    news_label(
+                        ui,
+                        &translated.summary,
+                        text_format(summary_font, theme.light_color, true),
+                        max_rows,
+                        reading.line_spacing,
+                    );
+                }
+            }
+
+            ui.add_space(spacing);
 
             ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
-                ui.label(RichText::new(&item.date).color(theme::LIGHT_COLOR));
+                if self.export_panel.is_selecting() {
+                    let mut is_selected = self.export_panel.selected.contains(item.key());
+                    if ui.checkbox(&mut is_selected, "").changed() {
+                        action = Some(ItemAction::ToggleSelect);
+                    }
+                }
+
+                let mut date = RichText::new(&item.date).color(theme.light_color);
+                if !self.conf.font.monospace.is_empty() {
+                    date = date.monospace();
+                }
+                ui.label(date);
+
+                if let Some(language) = badge {
+                    ui.add_space(theme::current().spacing);
+                    ui.label(
+                        RichText::new(format!(
+                            " {} · {} ",
+                            language.code().to_uppercase(),
+                            language.source().name()
+                        ))
+                        .small()
+                        .color(Color32::WHITE)
+                        .background_color(theme::current().badge_color),
+                    );
+                }
+
+                ui.add_space(theme::current().spacing);
+                let (icon, hint) = if self.bookmarks.contains(item) {
+                    ("★", "bookmark-remove")
+                } else {
+                    ("☆", "bookmark-add")
+                };
+
+                if ui
+                    .add(
+                        Button::new(RichText::new(icon).color(theme::current().brand_color))
+                            .frame(false),
+                    )
+                    .on_hover_text(tr(hint))
+                    .clicked()
+                {
+                    action = Some(ItemAction::ToggleBookmark);
+                }
+
+                if is_translatable && translated.is_none() {
+                    ui.add_space(theme::current().spacing);
+
+                    if self.translating.contains(item.key()) {
+                        ui.label(
+                            RichText::new(tr("translating")).color(theme::current().light_color),
+                        );
+                    } else if ui
+                        .add(
+                            Button::new(RichText::new("🌐").color(theme::current().brand_color))
+                                .frame(false),
+                        )
+                        .on_hover_text(tr("translate"))
+                        .clicked()
+                    {
+                        action = Some(ItemAction::Translate);
+                    }
+                }
 
                 if !item.link.is_empty() {
-                    ui.add_space(theme::SPACING);
+                    ui.add_space(theme::current().spacing);
 
-                    if ui.link(tr(self.conf.ui.is_cn, "原文链接")).clicked() {
+                    if ui.link(tr("source-link")).clicked() {
                         if let Err(e) = webbrowser::open(&item.link) {
                             log::warn!("{e:?}");
                         }
+                        action = Some(ItemAction::OpenLink);
                     }
                 }
             });
 
-            ui.add_space(theme::SPACING);
+            ui.add_space(spacing);
+        });
+
+        action
+    }
+
+    // Ctrl+/Ctrl- and pinch to zoom the news text, Ctrl+0 to reset it
+    fn handle_zoom(&mut self, ctx: &Context) {
+        let (zoom_delta, zoom_in, zoom_out, zoom_reset) = ctx.input_mut(|i| {
+            (
+                i.zoom_delta(),
+                i.consume_key(Modifiers::COMMAND, Key::PlusEquals),
+                i.consume_key(Modifiers::COMMAND, Key::Minus),
+                i.consume_key(Modifiers::COMMAND, Key::Num0),
+            )
         });
+
+        let reading = &mut self.conf.reading;
+        let font_scale = if zoom_reset {
+            1.0
+        } else if zoom_in {
+            reading.font_scale + FONT_SCALE_STEP
+        } else if zoom_out {
+            reading.font_scale - FONT_SCALE_STEP
+        } else {
+            reading.font_scale * zoom_delta
+        }
+        .clamp(MIN_FONT_SCALE, MAX_FONT_SCALE);
+
+        if font_scale != reading.font_scale {
+            reading.font_scale = font_scale;
+            self.is_zooming = true;
+        } else if self.is_zooming {
+            // save once the zooming is over rather than on every frame of a pinch
+            self.is_zooming = false;
+            self.save_conf();
+        }
     }
 
-    fn update_data(&mut self) {
+    fn update_data(&mut self, ctx: &Context) {
         let rx = self.rx.clone();
 
-        if let Ok(item) = rx.borrow_mut().try_recv() {
+        // the files are loaded in the fetching threads as well
+        let corrupted = store::take_corrupted();
+        if !corrupted.is_empty() {
+            let msg = corrupted
+                .iter()
+                .map(|c| c.message())
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.show_message(msg, MsgType::Danger);
+        }
+
+        while let Ok(item) = rx.borrow_mut().try_recv() {
             match item {
                 ChannelItem::ErrMsg(msg) => self.show_message(msg, MsgType::Warn),
-                ChannelItem::NewsItems((is_cn, items)) => {
+                ChannelItem::FetchDone => self.is_fetching = false,
+                ChannelItem::ConfigChanged => {
+                    self.reload_conf(ctx);
+                }
+                ChannelItem::IconsLoaded(icons) => {
+                    self.brand_icon = Some(icons.brand);
+                    self.refresh_icon = Some(icons.refresh);
+                    self.language_icon = Some(icons.language);
+                    self.about_icon = Some(icons.about);
+                    self.back_icon = Some(icons.back);
+                    self.reset_icon = Some(icons.reset);
+                }
+                ChannelItem::StateLoaded(state) => {
+                    (self.bookmarks, self.read_state, self.translations) = *state;
+                }
+                // the fetched news is newer than the cache
+                ChannelItem::CacheLoaded((language, items)) => {
+                    if self.feed(language).is_empty() {
+                        match language {
+                            Language::Cn => self.news_items_cn = items,
+                            Language::En => self.news_items_en = items,
+                        }
+                        self.timeline.borrow_mut().rows = None;
+                    }
+                }
+                ChannelItem::LoadDone => {
+                    self.is_loading = false;
+                    self.fetch_data();
+                }
+                ChannelItem::Translated((target, item, translated)) => {
+                    self.translating.remove(item.key());
+                    self.translations.insert(&target, &item, translated);
+                }
+                ChannelItem::TranslateFailed((key, reason)) => {
+                    self.translating.remove(&key);
+                    self.show_message(
+                        tr_args(
+                            "translate-failed",
+                            Some(&FluentArgs::from_iter([("reason", reason)])),
+                        ),
+                        MsgType::Warn,
+                    );
+                }
+                ChannelItem::NewsItems((language, items)) => {
                     if !items.is_empty() {
-                        if is_cn {
-                            self.news_items_cn = items;
-                        } else {
-                            self.news_items_en = items;
+                        match language {
+                            Language::Cn => self.news_items_cn = items,
+                            Language::En => self.news_items_en = items,
                         }
+                        self.timeline.borrow_mut().rows = None;
                     }
                 }
             }
+        }
+    }
 
-            self.is_fetching = false;
-        };
+    // Fetch the news again once the refresh interval has passed since the last fetch
+    fn auto_refresh(&mut self, ctx: &Context) {
+        let interval = self.conf.feed.refresh_interval_mins as i64 * 60;
+        if interval == 0 || self.is_loading {
+            return;
+        }
+
+        if util::timestamp() - self.fetched_at >= interval {
+            self.fetch_data();
+        }
+
+        let remaining = interval - (util::timestamp() - self.fetched_at);
+        ctx.request_repaint_after(Duration::from_secs(remaining.max(1) as u64));
+    }
+
+    // Generate the digest once its hour has come, also without any fetch
+    fn scheduled_digest(&mut self, ctx: &Context) {
+        if !self.conf.digest.enabled || self.is_loading {
+            return;
+        }
+
+        let date = digest::yesterday();
+        if Local::now().hour() >= self.conf.digest.hour && self.digest_date != date {
+            self.digest_date = date;
+
+            let conf = self.conf.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = digest::generate_if_due(&conf) {
+                    log::warn!("{e:?}");
+                }
+            });
+        }
+
+        ctx.request_repaint_after(digest::until_schedule(&self.conf));
     }
 
     fn fetch_data(&mut self) {
@@ -344,38 +1216,53 @@ impl App {
         }
 
         self.is_fetching = true;
+        self.fetched_at = util::timestamp();
         let tx = self.tx.clone();
-        let is_cn = self.conf.ui.is_cn;
-        let cache_dir = self.conf.cache_dir.clone();
+        let ctx = self.ctx.clone();
+        let languages = self.shown_languages();
+        let conf = self.conf.clone();
 
         std::thread::spawn(move || {
-            match if is_cn {
-                news::fetch_odaily(cache_dir.join("news-cn.json").as_path())
-            } else {
-                news::fetch_cryptocompare(cache_dir.join("news-en.json").as_path())
-            } {
-                Err(e) => {
-                    let _ = tx.try_send(ChannelItem::ErrMsg(e.to_string()));
-                }
-                Ok(v) => {
-                    let _ = tx.try_send(ChannelItem::NewsItems((is_cn, v)));
+            for language in languages {
+                match news::fetch(&conf, language.source()) {
+                    Err(e) => {
+                        let _ = tx.try_send(ChannelItem::ErrMsg(e.to_string()));
+                    }
+                    Ok(v) => {
+                        let _ = tx.send(ChannelItem::NewsItems((language, v)));
+                    }
                 }
+                ctx.request_repaint();
+            }
+            // the news and the end of the fetch wait for the channel rather than being dropped,
+            // or else `is_fetching` would never be reset
+            let _ = tx.send(ChannelItem::FetchDone);
+            ctx.request_repaint();
+
+            if let Err(e) = digest::generate_if_due(&conf) {
+                log::warn!("{e:?}");
             }
         });
     }
 
     fn popup_message(&mut self, ctx: &Context) {
-        let mut is_show = util::timestamp() - self.msg_spec.timestamp < 5_i64;
+        let shown_secs = util::timestamp() - self.msg_spec.timestamp;
+        let mut is_show = shown_secs < MSG_SECS;
+
+        // hide the message in time, while no other repaint happens
+        if is_show {
+            ctx.request_repaint_after(Duration::from_secs((MSG_SECS - shown_secs) as u64));
+        }
 
         let frame = Frame::none()
             .fill(match self.msg_spec.msg_type {
-                MsgType::Success => theme::SUCCESS_COLOR,
-                MsgType::Warn => theme::WARN_COLOR,
-                MsgType::Danger => theme::DANGER_COLOR,
-                _ => theme::INFO_COLOR,
+                MsgType::Success => theme::current().success_color,
+                MsgType::Warn => theme::current().warn_color,
+                MsgType::Danger => theme::current().danger_color,
+                _ => theme::current().info_color,
             })
             .rounding(0.0)
-            .inner_margin(theme::PADDING)
+            .inner_margin(theme::current().padding)
             .stroke(Stroke {
                 width: 1.0,
                 color: Color32::BLACK,
@@ -388,20 +1275,67 @@ impl App {
             .auto_sized()
             .constrain(true)
             .interactable(false)
-            .fixed_pos(Pos2::new(theme::PADDING, theme::PADDING))
+            .fixed_pos(Pos2::new(
+                theme::current().padding,
+                theme::current().padding,
+            ))
             .frame(frame)
             .show(ctx, |ui| {
                 ui.label(&self.msg_spec.msg);
             });
     }
 
-    fn show_message(&mut self, msg: String, msg_type: MsgType) {
+    pub fn show_message(&mut self, msg: String, msg_type: MsgType) {
         self.msg_spec.msg = msg;
         self.msg_spec.msg_type = msg_type;
         self.msg_spec.timestamp = util::timestamp();
     }
 }
 
+fn text_format(font_id: FontId, color: Color32, italics: bool) -> TextFormat {
+    TextFormat {
+        font_id,
+        color,
+        italics,
+        ..Default::default()
+    }
+}
+
+// Show the wrapped text, `max_rows` 0 for no limit. egui has no line height setting, so the rows
+// of the laid out text are moved apart for a line spacing above 1
+fn news_label(ui: &mut Ui, text: &str, format: TextFormat, max_rows: usize, line_spacing: f32) {
+    let font_size = format.font_id.size;
+    let mut job = LayoutJob::single_section(text.to_string(), format);
+    job.wrap = TextWrapping {
+        max_width: ui.available_width(),
+        max_rows,
+        ..Default::default()
+    };
+
+    let galley = ui.fonts(|f| f.layout_job(job));
+    let extra = font_size * (line_spacing - 1.0);
+    if extra <= 0.0 || galley.rows.len() < 2 {
+        ui.label(galley);
+        return;
+    }
+
+    let mut galley = (*galley).clone();
+    for (index, row) in galley.rows.iter_mut().enumerate() {
+        let delta = Vec2::new(0.0, extra * index as f32);
+        row.rect = row.rect.translate(delta);
+        row.visuals.mesh.translate(delta);
+        row.visuals.mesh_bounds = row.visuals.mesh_bounds.translate(delta);
+        for glyph in row.glyphs.iter_mut() {
+            glyph.pos += delta;
+        }
+    }
+
+    let height = extra * (galley.rows.len() - 1) as f32;
+    galley.rect.max.y += height;
+    galley.mesh_bounds.max.y += height;
+    ui.label(Arc::new(galley));
+}
+
 #[allow(unused)]
 pub fn is_mobile(ctx: &egui::Context) -> bool {
     let screen_size = ctx.screen_rect().size();
@@ -0,0 +1,56 @@
+use super::{
+    news::{self, Language, NewsItem},
+    store,
+};
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Local};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn path(data_dir: &Path, language: Language) -> PathBuf {
+    data_dir
+        .join("archive")
+        .join(format!("news-{}.json", language.code()))
+}
+
+pub fn load(path: &Path) -> Vec<NewsItem> {
+    news::load_items(path)
+}
+
+// Merge the fetched items into the archive and drop the items older than `retention_days`
+pub fn update(path: &Path, items: &[NewsItem], retention_days: i64) -> Result<Vec<NewsItem>> {
+    let mut archive_items = load(path);
+    let keys = archive_items
+        .iter()
+        .map(|item| item.key().to_string())
+        .collect::<HashSet<_>>();
+
+    archive_items.extend(
+        items
+            .iter()
+            .filter(|item| !keys.contains(item.key()))
+            .cloned(),
+    );
+
+    // the sources are in different time zones, so the items are compared in the local one
+    let oldest_date = (Local::now() - Duration::days(retention_days))
+        .format("%Y-%m-%d")
+        .to_string();
+    archive_items.retain(|item| item.local_date() >= oldest_date);
+    archive_items.sort_by_key(|item| Reverse(item.published_at()));
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    match serde_json::to_string_pretty(&archive_items) {
+        Ok(text) => store::write(path, text)?,
+        Err(e) => return Err(anyhow!("{e:?}")),
+    }
+
+    Ok(archive_items)
+}
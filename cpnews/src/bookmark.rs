@@ -0,0 +1,160 @@
+use super::{
+    app::{App, CurrentPanel, ItemAction},
+    news::NewsItem,
+    store, theme,
+    tr::tr,
+    util,
+};
+use anyhow::{anyhow, Result};
+use egui::{FontId, RichText, ScrollArea, TextEdit, Ui};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Bookmark {
+    pub item: NewsItem,
+    pub note: String,
+    pub created_at: i64,
+}
+
+// Bookmarks are kept in their own file, so trimming the news cache never drops them
+#[derive(Clone, Debug, Default)]
+pub struct Bookmarks {
+    path: PathBuf,
+    pub items: Vec<Bookmark>,
+    pub note_edits: util::Debounce,
+}
+
+impl Bookmarks {
+    pub fn load(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            items: store::load::<Vec<Bookmark>>(path).unwrap_or_default(),
+            note_edits: Default::default(),
+        }
+    }
+
+    // Save the notes still being edited
+    pub fn flush(&mut self) {
+        if self.note_edits.flush() {
+            if let Err(e) = self.save() {
+                log::warn!("{e:?}");
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        match serde_json::to_string_pretty(&self.items) {
+            Ok(text) => store::write(&self.path, text),
+            Err(e) => Err(anyhow!("{e:?}")),
+        }
+    }
+
+    pub fn contains(&self, item: &NewsItem) -> bool {
+        self.items.iter().any(|b| b.item.key() == item.key())
+    }
+
+    pub fn toggle(&mut self, item: &NewsItem) {
+        if self.contains(item) {
+            self.items.retain(|b| b.item.key() != item.key());
+        } else {
+            // newest bookmark first
+            self.items.insert(
+                0,
+                Bookmark {
+                    item: item.clone(),
+                    note: String::default(),
+                    created_at: util::timestamp(),
+                },
+            );
+        }
+
+        if let Err(e) = self.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    if ui
+        .add(
+            theme::image_and_text_button(
+                &app.back_icon,
+                theme::BACK_ICON_SIZE,
+                RichText::new(tr("bookmarks"))
+                    .font(FontId::proportional(theme::current().news_title_font_size)),
+            )
+            .frame(false),
+        )
+        .clicked()
+    {
+        app.current_panel = CurrentPanel::News;
+    }
+
+    ui.add_space(theme::current().spacing);
+
+    if app.bookmarks.items.is_empty() {
+        ui.vertical_centered(|ui| {
+            ui.add_space(theme::current().spacing * 4.);
+            ui.label(RichText::new(tr("bookmarks-empty")).color(theme::current().light_color));
+        });
+        return;
+    }
+
+    let mut removed = None;
+    let mut is_changed = false;
+
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for index in 0..app.bookmarks.items.len() {
+                match app.show_news_item(ui, &app.bookmarks.items[index].item, None) {
+                    Some(ItemAction::ToggleBookmark) => removed = Some(index),
+                    Some(ItemAction::ToggleSelect) => app
+                        .export_panel
+                        .toggle_selected(&app.bookmarks.items[index].item),
+                    Some(ItemAction::OpenLink)
+                        if app.read_state.mark_read([&app.bookmarks.items[index].item]) =>
+                    {
+                        if let Err(e) = app.read_state.save() {
+                            log::warn!("{e:?}");
+                        }
+                    }
+                    Some(ItemAction::Translate) => {
+                        let item = app.bookmarks.items[index].item.clone();
+                        app.translate(None, item);
+                    }
+                    _ => (),
+                }
+
+                let bookmark = &mut app.bookmarks.items[index];
+                let response = ui.add(
+                    TextEdit::multiline(&mut bookmark.note)
+                        .desired_rows(1)
+                        .desired_width(f32::INFINITY)
+                        .hint_text(tr("bookmark-note-hint")),
+                );
+
+                if response.changed() {
+                    app.bookmarks.note_edits.touch();
+                }
+
+                if response.lost_focus() {
+                    is_changed |= app.bookmarks.note_edits.flush();
+                }
+
+                ui.add_space(theme::current().spacing);
+                ui.separator();
+            }
+        });
+
+    is_changed |= app.bookmarks.note_edits.is_due(ui.ctx());
+
+    if let Some(index) = removed {
+        let item = app.bookmarks.items[index].item.clone();
+        app.bookmarks.toggle(&item);
+    } else if is_changed {
+        if let Err(e) = app.bookmarks.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
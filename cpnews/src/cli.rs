@@ -0,0 +1,269 @@
+use super::{
+    archive,
+    config::{Config, Dirs},
+    digest,
+    export::{self, Format},
+    hook,
+    news::{self, Language, NewsItem, Source},
+    server, tr, tui, version, webhook,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{collections::HashSet, path::PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(name = "cpnews", version = version::VERSION, about = "A crypto news reader")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Directory of cpnews.conf and the themes
+    #[arg(long, global = true, env = "CPNEWS_CONFIG_DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Directory of the bookmarks, the archive and the other data
+    #[arg(long, global = true, env = "CPNEWS_DATA_DIR")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Directory of the news cache. Defaults to `cache` in the data directory
+    #[arg(long, global = true, env = "CPNEWS_CACHE_DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Keep everything next to the executable, also enabled by a `portable` file there
+    #[arg(long, global = true, env = "CPNEWS_PORTABLE")]
+    pub portable: bool,
+}
+
+impl Cli {
+    pub fn dirs(&self) -> Dirs {
+        Dirs {
+            config_dir: self.config_dir.clone(),
+            data_dir: self.data_dir.clone(),
+            cache_dir: self.cache_dir.clone(),
+            portable: self.portable,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Lang {
+    Cn,
+    En,
+}
+
+impl From<Lang> for Language {
+    fn from(lang: Lang) -> Self {
+        match lang {
+            Lang::Cn => Language::Cn,
+            Lang::En => Language::En,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+    Csv,
+    Html,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Fetch the latest news and update the cache
+    Fetch {
+        /// Source to fetch, e.g. odaily or cryptocompare. Defaults to all the sources
+        #[arg(long)]
+        source: Option<Source>,
+
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// List the cached news
+    List {
+        /// Language of the news. Defaults to the language of the app
+        #[arg(long, value_enum)]
+        lang: Option<Lang>,
+
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Search the cached and archived news
+    Search {
+        query: String,
+
+        /// Language of the news. Defaults to all the languages
+        #[arg(long, value_enum)]
+        lang: Option<Lang>,
+
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Generate the daily digest from the archive
+    Digest {
+        /// Date of the digest, e.g. 2023-07-12. Defaults to yesterday
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Fetch the latest news into the archive before generating the digest
+        #[arg(long)]
+        fetch: bool,
+    },
+
+    /// Show the news in the terminal
+    Tui,
+
+    /// Serve the news as RSS, Atom and JSON feeds
+    Serve {
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Seconds between two fetches of the news
+        #[arg(long, default_value_t = 600)]
+        interval: u64,
+    },
+}
+
+fn print_items(items: &[NewsItem], format: OutputFormat) -> Result<()> {
+    let format = match format {
+        OutputFormat::Text => {
+            for item in items.iter() {
+                println!(
+                    "{}\n{} · {}\n{}\n",
+                    item.title, item.date, item.source, item.link
+                );
+            }
+            return Ok(());
+        }
+        OutputFormat::Json => Format::Json,
+        OutputFormat::Markdown => Format::Markdown,
+        OutputFormat::Csv => Format::Csv,
+        OutputFormat::Html => Format::Html,
+    };
+
+    // the last line ends as well, for the line based tools, e.g. `wc -l`
+    let text = export::to_string(items, format)?;
+    if text.ends_with('\n') {
+        print!("{text}");
+    } else {
+        println!("{text}");
+    }
+    Ok(())
+}
+
+// The cached news goes first, followed by the archived news which is not in the cache
+fn cached_and_archived_items(conf: &Config, language: Language) -> Vec<NewsItem> {
+    let mut items = news::load_items(&news::cache_path(&conf.cache_dir, language));
+
+    let mut keys = items
+        .iter()
+        .map(|item| item.key().to_string())
+        .collect::<HashSet<_>>();
+
+    for item in archive::load(&archive::path(&conf.data_dir, language)) {
+        if keys.insert(item.key().to_string()) {
+            items.push(item);
+        }
+    }
+
+    items
+}
+
+// The webhooks and the hooks of the fetched news run in the background
+fn wait_for_notifications() {
+    webhook::wait();
+    hook::wait();
+}
+
+pub fn run(command: Command, dirs: &Dirs) -> Result<()> {
+    let mut conf = Config::default();
+    conf.init(dirs)?;
+
+    tr::set_locale(&conf.ui.locale);
+    for warning in conf.warnings.iter() {
+        eprintln!("{}", warning.message());
+    }
+
+    match command {
+        Command::Fetch { source, format } => {
+            let source_is_all = source.is_none();
+            let sources = source.map_or(Source::ALL.to_vec(), |s| vec![s]);
+
+            let mut items = vec![];
+            for source in sources.into_iter() {
+                match news::fetch(&conf, source) {
+                    Ok(v) => items.extend(v),
+                    // keep fetching the other sources when no source is specified
+                    Err(e) if source_is_all => log::warn!("{}: {e}", source.name()),
+                    Err(e) => return Err(e),
+                }
+            }
+
+            print_items(&items, format)?;
+            wait_for_notifications();
+        }
+        Command::List {
+            lang,
+            limit,
+            format,
+        } => {
+            let language = lang.map_or(conf.ui.language, Language::from);
+            let items = news::load_items(&news::cache_path(&conf.cache_dir, language));
+
+            print_items(&items[..items.len().min(limit)], format)?;
+        }
+        Command::Search {
+            query,
+            lang,
+            limit,
+            format,
+        } => {
+            let languages = lang.map_or(Language::ALL.to_vec(), |l| vec![Language::from(l)]);
+
+            let items = languages
+                .into_iter()
+                .flat_map(|language| cached_and_archived_items(&conf, language))
+                .filter(|item| news::matches(item, &query))
+                .take(limit)
+                .collect::<Vec<_>>();
+
+            print_items(&items, format)?;
+        }
+        Command::Digest { date, fetch } => {
+            if fetch {
+                for source in Source::ALL {
+                    if let Err(e) = news::fetch(&conf, source) {
+                        log::warn!("{e:?}");
+                    }
+                }
+            }
+
+            let date = date.unwrap_or_else(digest::yesterday);
+            let path = digest::generate(&conf, &date)?;
+            println!("{}", path.display());
+            wait_for_notifications();
+        }
+        Command::Tui => tui::run(conf)?,
+        Command::Serve {
+            host,
+            port,
+            interval,
+        } => server::run(conf, &host, port, interval)?,
+    }
+
+    Ok(())
+}
@@ -1,5 +1,35 @@
+use super::{
+    news::Language,
+    store::{self, Corrupted},
+    theme,
+    tr::tr_args,
+    zh::ChineseScript,
+};
 use anyhow::{anyhow, Result};
-use std::{env, fs};
+use fluent_bundle::FluentArgs;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fmt, fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+// The app runs in the portable mode if this file is next to the executable
+const PORTABLE_FILE: &str = "portable";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+// Version of the config layout, which is increased with a migration added to `MIGRATIONS`
+pub const CONFIG_VERSION: u32 = 1;
+
+// The migration at index `n` upgrades the config of version `n` to version `n + 1`
+const MIGRATIONS: [fn(&mut Value); CONFIG_VERSION as usize] = [migrate_v0];
 
 #[allow(unused_imports)]
 use std::path::PathBuf;
@@ -26,8 +56,22 @@ impl AppDirs {
     }
 }
 
+// Directories given on the command line or by the `CPNEWS_*` environment variables, the ones not
+// given are resolved by the platform, or next to the executable in the portable mode
+#[derive(Clone, Debug, Default)]
+pub struct Dirs {
+    pub config_dir: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub portable: bool,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct Config {
+    // 0 for the config files written before the versioning
+    #[serde(default)]
+    pub version: u32,
+
     #[serde(skip)]
     pub working_dir: PathBuf,
 
@@ -37,77 +81,851 @@ pub struct Config {
     #[serde(skip)]
     pub db_path: PathBuf,
 
+    #[serde(skip)]
+    pub data_dir: PathBuf,
+
     #[serde(skip)]
     pub cache_dir: PathBuf,
 
+    #[serde(default)]
     pub ui: UI,
+
+    #[serde(default)]
+    pub reading: Reading,
+
+    #[serde(default)]
+    pub font: Font,
+
+    #[serde(default)]
+    pub feed: Feed,
+
+    #[serde(default)]
+    pub network: Network,
+
+    #[serde(default)]
+    pub archive: Archive,
+
+    #[serde(default)]
+    pub digest: Digest,
+
+    #[serde(default)]
+    pub translation: Translation,
+
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
+
+    // problems found while loading the config, which are shown to the user
+    #[serde(skip)]
+    pub warnings: Vec<ConfigWarning>,
+
+    // the file is written by a newer version of the app, saving it would drop the settings this
+    // version does not know. Or the file can not be read, and saving it would lose the settings
+    #[serde(skip)]
+    pub is_read_only: bool,
+
+    // hash of the file as it was last read or written, shared by the clones of the config, so
+    // that the changes made by other programs are found
+    #[serde(skip)]
+    file_hash: Arc<Mutex<Option<u64>>>,
+}
+
+// The config file is changed by another program since it was last read, so it is not saved
+#[derive(Debug)]
+pub struct ConfigConflict;
+
+impl fmt::Display for ConfigConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the config file is changed by another program")
+    }
+}
+
+impl std::error::Error for ConfigConflict {}
+
+// The config file is written by a newer version of the app or can not be read, so it is not saved
+#[derive(Debug)]
+pub struct ConfigReadOnly;
+
+impl fmt::Display for ConfigReadOnly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the config file is read-only")
+    }
+}
+
+impl std::error::Error for ConfigReadOnly {}
+
+#[derive(Debug, Clone)]
+pub enum ConfigWarning {
+    Migrated { from: u32, backup: PathBuf },
+
+    // the file is written by a newer version of the app
+    Newer { version: u32 },
+
+    // the settings which can not be read and are reset to the defaults
+    InvalidFields { fields: Vec<String> },
+
+    // the file is corrupted and moved aside, and the defaults are used
+    Corrupted(Corrupted),
+
+    // the file can not be read, e.g. for the permissions, so the defaults are used and the file
+    // is not changed
+    Unreadable { error: String },
+}
+
+impl ConfigWarning {
+    pub fn message(&self) -> String {
+        let mut args = FluentArgs::new();
+        let id = match self {
+            ConfigWarning::Migrated { from, backup } => {
+                args.set("from", from);
+                args.set("path", backup.display().to_string());
+                "config-migrated"
+            }
+            ConfigWarning::Newer { version } => {
+                args.set("version", version);
+                "config-newer"
+            }
+            ConfigWarning::InvalidFields { fields } => {
+                args.set("fields", fields.join(", "));
+                "config-invalid-fields"
+            }
+            ConfigWarning::Corrupted(corrupted) => return corrupted.message(),
+            ConfigWarning::Unreadable { error } => {
+                args.set("error", error.as_str());
+                "config-unreadable"
+            }
+        };
+
+        tr_args(id, Some(&args))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct UI {
-    pub is_cn: bool,
+    // locale of the texts, e.g. `en` or `zh-CN`
+    pub locale: String,
+
+    // language of the news
+    pub language: Language,
+
+    // show the news of all the languages in one timeline
+    pub timeline: bool,
+
+    // script of the displayed Chinese news
+    pub chinese_script: ChineseScript,
+
+    // `light`, `dark`, `system` or the name of a theme file in the themes directory
+    pub theme: String,
 }
 
 impl Default for UI {
     fn default() -> Self {
-        Self { is_cn: true }
+        Self {
+            locale: "zh-CN".to_string(),
+            language: Language::Cn,
+            timeline: false,
+            chinese_script: ChineseScript::Simplified,
+            theme: theme::LIGHT_THEME.to_string(),
+        }
     }
 }
 
-impl Config {
-    pub fn init(&mut self) -> Result<()> {
-        let app_name = if cfg!(not(target_os = "android")) {
-            "cpnews"
-        } else {
-            "xyz.heng30.cpnews"
-        };
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Density {
+    #[default]
+    Comfortable,
+
+    // the summary is cut to two lines
+    Compact,
+
+    // the summary is hidden
+    HeadlinesOnly,
+}
+
+impl Density {
+    pub const ALL: [Density; 3] = [
+        Density::Comfortable,
+        Density::Compact,
+        Density::HeadlinesOnly,
+    ];
+
+    pub fn tr_id(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "density-comfortable",
+            Density::Compact => "density-compact",
+            Density::HeadlinesOnly => "density-headlines-only",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Reading {
+    // scale of the news font sizes of the theme
+    pub font_scale: f32,
+
+    // line height in multiples of the font size
+    pub line_spacing: f32,
 
-        let app_dirs = AppDirs::new(Some(app_name), true).unwrap();
+    pub density: Density,
+}
+
+impl Default for Reading {
+    fn default() -> Self {
+        Self {
+            font_scale: 1.0,
+            line_spacing: 1.0,
+            density: Density::Comfortable,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Font {
+    // file path or name of a system font, a Chinese font of the system or else the embedded font
+    // is used if empty
+    pub proportional: String,
+
+    // font of the dates and numbers, the egui font is used if empty
+    pub monospace: String,
+
+    // fonts for the emoji and symbols missing in the fonts above, the missing ones are skipped
+    pub fallbacks: Vec<String>,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            proportional: String::default(),
+            monospace: String::default(),
+            fallbacks: ["Symbola", "seguisym", "NotoSansSymbols2-Regular"]
+                .map(|f| f.to_string())
+                .to_vec(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Feed {
+    // minutes between two fetches while the app is running, 0 to fetch on demand only
+    pub refresh_interval_mins: u64,
+
+    // the news matching any of the keywords is hidden
+    pub muted_keywords: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Network {
+    pub timeout_secs: u64,
+
+    // url of a http proxy, e.g. `http://127.0.0.1:8080`, no proxy if empty
+    pub proxy: String,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            proxy: String::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Archive {
+    pub retention_days: i64,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Digest {
+    pub enabled: bool,
+
+    // local hour after which the digest of the previous day is generated
+    pub hour: u32,
+
+    // max items of each topic
+    pub top: usize,
+}
+
+impl Default for Digest {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hour: 8,
+            top: 5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Translation {
+    pub enabled: bool,
+
+    // base url of a LibreTranslate compatible server
+    pub url: String,
+
+    #[serde(default)]
+    pub api_key: String,
+
+    // language code of the translations, e.g. `en` or `zh`
+    pub target: String,
+}
+
+impl Default for Translation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://localhost:5000".to_string(),
+            api_key: String::default(),
+            target: "en".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+
+    // only the news matching the query is posted
+    #[serde(default)]
+    pub query: String,
+
+    // key of the HMAC-SHA256 signature header
+    #[serde(default)]
+    pub secret: String,
+}
+
+impl Config {
+    // The default settings with the paths of this config
+    pub fn defaults(&self) -> Self {
+        Self {
+            working_dir: self.working_dir.clone(),
+            config_path: self.config_path.clone(),
+            db_path: self.db_path.clone(),
+            data_dir: self.data_dir.clone(),
+            cache_dir: self.cache_dir.clone(),
+            version: CONFIG_VERSION,
+            is_read_only: self.is_read_only,
+            file_hash: self.file_hash.clone(),
+            ..Default::default()
+        }
+    }
 
-        self.init_app_dir(&app_dirs)?;
+    pub fn init(&mut self, dirs: &Dirs) -> Result<()> {
+        self.init_app_dir(dirs)?;
         self.load()?;
         log::debug!("{:?}", self);
         Ok(())
     }
 
-    fn init_app_dir(&mut self, app_dirs: &AppDirs) -> Result<()> {
-        self.config_path = app_dirs.config_dir.join("cpnews.conf");
-        self.db_path = app_dirs.data_dir.join("cpnews.db");
-
-        self.cache_dir = app_dirs.data_dir.join("cache");
+    fn init_app_dir(&mut self, dirs: &Dirs) -> Result<()> {
         self.working_dir = {
             let mut dir = env::current_exe()?;
             dir.pop();
             dir
         };
 
-        fs::create_dir_all(&app_dirs.config_dir)?;
-        fs::create_dir_all(&app_dirs.data_dir)?;
+        let (config_dir, data_dir) =
+            if dirs.portable || self.working_dir.join(PORTABLE_FILE).exists() {
+                (
+                    self.working_dir.join("config"),
+                    self.working_dir.join("data"),
+                )
+            } else {
+                let app_name = if cfg!(not(target_os = "android")) {
+                    "cpnews"
+                } else {
+                    "xyz.heng30.cpnews"
+                };
+
+                let app_dirs = AppDirs::new(Some(app_name), true).unwrap();
+                (app_dirs.config_dir, app_dirs.data_dir)
+            };
+
+        let config_dir = dirs.config_dir.clone().unwrap_or(config_dir);
+        let data_dir = dirs.data_dir.clone().unwrap_or(data_dir);
+        let cache_dir = dirs.cache_dir.clone().unwrap_or(data_dir.join("cache"));
+
+        self.config_path = config_dir.join("cpnews.conf");
+        self.db_path = data_dir.join("cpnews.db");
+        self.data_dir = data_dir;
+        self.cache_dir = cache_dir;
+
+        fs::create_dir_all(&config_dir)?;
+        fs::create_dir_all(&self.data_dir)?;
         fs::create_dir_all(&self.cache_dir)?;
 
         Ok(())
     }
 
     fn load(&mut self) -> Result<()> {
-        match fs::read_to_string(&self.config_path) {
-            Ok(text) => match serde_json::from_str::<Config>(&text) {
-                Ok(c) => {
-                    self.ui = c.ui;
-                    Ok(())
+        self.version = CONFIG_VERSION;
+
+        // a corrupted file falls back to the previous generation, or else to the defaults
+        let (text, value) = match store::read_with(&self.config_path, parse_config) {
+            Some(loaded) => loaded,
+            _ => {
+                if let Some(corrupted) = store::take_corrupted_of(&self.config_path) {
+                    self.warnings.push(ConfigWarning::Corrupted(corrupted));
+                    return self.save();
                 }
-                Err(e) => Err(anyhow!("{e:?}")),
-            },
-            Err(_) => match serde_json::to_string_pretty(self) {
-                Ok(text) => Ok(fs::write(&self.config_path, text)?),
-                Err(e) => Err(anyhow!("{e:?}")),
-            },
+
+                return match fs::read(&self.config_path) {
+                    Err(e) if e.kind() != ErrorKind::NotFound => {
+                        self.is_read_only = true;
+                        self.warnings.push(ConfigWarning::Unreadable {
+                            error: e.to_string(),
+                        });
+                        Ok(())
+                    }
+                    _ => self.save(),
+                };
+            }
+        };
+
+        *self.file_hash.lock().unwrap() = Some(text_hash(&text));
+        self.load_value(&text, value)
+    }
+
+    // Read the config file changed by another program. Unlike the first load, an unreadable file
+    // is left alone, as it may be still being written
+    pub fn reload(&self) -> Result<Config> {
+        let text = fs::read_to_string(&self.config_path)?;
+        let (text, value) =
+            parse_config(&text).ok_or(anyhow!("{} is unreadable", self.config_path.display()))?;
+
+        let mut conf = self.defaults();
+        *conf.file_hash.lock().unwrap() = Some(text_hash(&text));
+        conf.load_value(&text, value)?;
+        Ok(conf)
+    }
+
+    fn load_value(&mut self, text: &str, mut value: Value) -> Result<()> {
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or_default() as u32;
+
+        self.is_read_only = version > CONFIG_VERSION;
+        if self.is_read_only {
+            self.warnings.push(ConfigWarning::Newer { version });
+        }
+
+        let is_migrated = version < CONFIG_VERSION;
+        if is_migrated {
+            let backup = store::with_suffix(&self.config_path, &format!("v{version}.bak"));
+            fs::write(&backup, text)?;
+
+            for migrate in MIGRATIONS[version as usize..].iter() {
+                migrate(&mut value);
+            }
+            self.warnings.push(ConfigWarning::Migrated {
+                from: version,
+                backup,
+            });
+        }
+
+        // an invalid setting is reset to its default, rather than failing the whole config
+        let mut invalid_fields = vec![];
+        self.ui = load_section(&value, "ui", &mut invalid_fields);
+        self.reading = load_section(&value, "reading", &mut invalid_fields);
+        self.font = load_section(&value, "font", &mut invalid_fields);
+        self.feed = load_section(&value, "feed", &mut invalid_fields);
+        self.network = load_section(&value, "network", &mut invalid_fields);
+        self.archive = load_section(&value, "archive", &mut invalid_fields);
+        self.digest = load_section(&value, "digest", &mut invalid_fields);
+        self.translation = load_section(&value, "translation", &mut invalid_fields);
+        self.webhooks = load_list(&value, "webhooks", &mut invalid_fields);
+        self.hooks = load_list(&value, "hooks", &mut invalid_fields);
+
+        if !invalid_fields.is_empty() {
+            log::warn!("invalid config fields: {invalid_fields:?}");
+            self.warnings.push(ConfigWarning::InvalidFields {
+                fields: invalid_fields,
+            });
+        }
+
+        if is_migrated {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    // True if another program changed the config file since it was last read or written
+    pub fn is_changed_on_disk(&self) -> bool {
+        let file_hash = *self.file_hash.lock().unwrap();
+        match fs::read_to_string(&self.config_path) {
+            Ok(text) => file_hash.is_some_and(|hash| hash != text_hash(&text)),
+            _ => false,
         }
     }
 
+    // The changes made by another program are not overwritten, `ConfigConflict` is returned instead
     pub fn save(&self) -> Result<()> {
+        if self.is_read_only {
+            return Err(ConfigReadOnly.into());
+        }
+
+        if self.is_changed_on_disk() {
+            return Err(ConfigConflict.into());
+        }
+
         match serde_json::to_string_pretty(self) {
-            Ok(text) => Ok(fs::write(&self.config_path, text)?),
+            Ok(text) => {
+                store::write(&self.config_path, &text)?;
+                *self.file_hash.lock().unwrap() = Some(text_hash(&text));
+                Ok(())
+            }
             Err(e) => Err(anyhow!("{e:?}")),
         }
     }
 }
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Poll the modified time of the config file, `on_change` is called once it is changed. The
+// writes of the app itself are reported as well, `Config::is_changed_on_disk` tells them apart
+pub fn watch(path: PathBuf, on_change: impl Fn() + Send + 'static) {
+    thread::spawn(move || {
+        let mut modified = modified_time(&path);
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+
+            let now_modified = modified_time(&path);
+            if now_modified != modified {
+                modified = now_modified;
+                on_change();
+            }
+        }
+    });
+}
+
+// The text and the JSON object of the config file
+fn parse_config(text: &str) -> Option<(String, Value)> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) if value.is_object() => Some((text.to_string(), value)),
+        _ => None,
+    }
+}
+
+// Read the section with the valid fields only, the invalid fields are left to the defaults
+fn load_section<T: Serialize + DeserializeOwned + Default>(
+    conf: &Value,
+    key: &str,
+    invalid_fields: &mut Vec<String>,
+) -> T {
+    let value = match conf.get(key) {
+        Some(value) => value,
+        _ => return T::default(),
+    };
+
+    if let Ok(section) = serde_json::from_value::<T>(value.clone()) {
+        return section;
+    }
+
+    let mut fields = match serde_json::to_value(T::default()) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+
+    match value.as_object() {
+        Some(user_fields) => {
+            for (name, field) in user_fields.iter() {
+                let default = fields.insert(name.clone(), field.clone());
+                if serde_json::from_value::<T>(Value::Object(fields.clone())).is_ok() {
+                    continue;
+                }
+
+                invalid_fields.push(format!("{key}.{name}"));
+                match default {
+                    Some(default) => fields.insert(name.clone(), default),
+                    _ => fields.remove(name),
+                };
+            }
+        }
+        _ => invalid_fields.push(key.to_string()),
+    }
+
+    serde_json::from_value(Value::Object(fields)).unwrap_or_default()
+}
+
+// Read the valid items of the list, the invalid items are dropped
+fn load_list<T: DeserializeOwned>(
+    conf: &Value,
+    key: &str,
+    invalid_fields: &mut Vec<String>,
+) -> Vec<T> {
+    let items = match conf.get(key) {
+        Some(Value::Array(items)) => items,
+        Some(_) => {
+            invalid_fields.push(key.to_string());
+            return vec![];
+        }
+        _ => return vec![],
+    };
+
+    items
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(index, item)| match serde_json::from_value::<T>(item.clone()) {
+                Ok(item) => Some(item),
+                _ => {
+                    invalid_fields.push(format!("{key}[{index}]"));
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+// Version 0 to 1: the `ui.is_cn` setting of both the locale and the news language is split
+fn migrate_v0(conf: &mut Value) {
+    let ui = match conf.get_mut("ui").and_then(Value::as_object_mut) {
+        Some(ui) => ui,
+        _ => return,
+    };
+
+    if let Some(is_cn) = ui.remove("is_cn").and_then(|v| v.as_bool()) {
+        let (locale, language) = if is_cn { ("zh-CN", "cn") } else { ("en", "en") };
+        ui.insert("locale".to_string(), Value::from(locale));
+        ui.insert("language".to_string(), Value::from(language));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookMode {
+    // run the command for each new item
+    #[default]
+    Item,
+
+    // run the command once for all the new items
+    Batch,
+}
+
+impl HookMode {
+    pub const ALL: [HookMode; 2] = [HookMode::Item, HookMode::Batch];
+
+    pub fn tr_id(&self) -> &'static str {
+        match self {
+            HookMode::Item => "hook-mode-item",
+            HookMode::Batch => "hook-mode-batch",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hook {
+    pub command: String,
+
+    #[serde(default)]
+    pub mode: HookMode,
+
+    #[serde(default = "Hook::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Hook {
+    fn default_timeout_secs() -> u64 {
+        30
+    }
+}
+
+impl Default for Hook {
+    fn default() -> Self {
+        Self {
+            command: String::default(),
+            mode: HookMode::Item,
+            timeout_secs: Self::default_timeout_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A config in an empty directory of the test, which is removed by the next run
+    fn test_config(name: &str, text: Option<&[u8]>) -> Config {
+        let dir = env::temp_dir().join(format!("cpnews-config-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let conf = Config {
+            config_path: dir.join("cpnews.conf"),
+            ..Default::default()
+        };
+        if let Some(text) = text {
+            fs::write(&conf.config_path, text).unwrap();
+        }
+        conf
+    }
+
+    fn load(conf: &mut Config) -> Value {
+        conf.load().unwrap();
+        serde_json::from_str(&fs::read_to_string(&conf.config_path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn migrate_v0_is_cn() {
+        for (is_cn, locale, language) in
+            [(true, "zh-CN", Language::Cn), (false, "en", Language::En)]
+        {
+            let text = format!(r#"{{"ui": {{"is_cn": {is_cn}, "theme": "dark"}}}}"#);
+            let mut conf = test_config(&format!("v0-{is_cn}"), Some(text.as_bytes()));
+            let saved = load(&mut conf);
+
+            assert_eq!(conf.ui.locale, locale);
+            assert_eq!(conf.ui.language, language);
+            assert_eq!(conf.ui.theme, "dark");
+            assert_eq!(saved["version"], CONFIG_VERSION);
+            assert!(saved["ui"].get("is_cn").is_none());
+
+            let backup = store::with_suffix(&conf.config_path, "v0.bak");
+            assert_eq!(fs::read_to_string(&backup).unwrap(), text);
+            assert!(matches!(
+                conf.warnings[..],
+                [ConfigWarning::Migrated { from: 0, .. }]
+            ));
+        }
+    }
+
+    #[test]
+    fn migrate_again_overwrites_backup() {
+        let mut conf = test_config("v0-again", Some(br#"{"ui": {"is_cn": true}}"#));
+        load(&mut conf);
+
+        fs::write(&conf.config_path, br#"{"ui": {"is_cn": false}}"#).unwrap();
+        let mut conf = Config {
+            config_path: conf.config_path.clone(),
+            ..Default::default()
+        };
+        load(&mut conf);
+
+        let backup = store::with_suffix(&conf.config_path, "v0.bak");
+        assert_eq!(
+            fs::read_to_string(&backup).unwrap(),
+            r#"{"ui": {"is_cn": false}}"#
+        );
+        assert!(!store::backup_path(&backup).exists());
+    }
+
+    #[test]
+    fn invalid_fields_are_reset() {
+        let text = br#"{
+            "version": 1,
+            "ui": {"locale": "en", "timeline": "yes"},
+            "feed": {"refresh_interval_mins": 5, "muted_keywords": "spam"},
+            "network": 3,
+            "webhooks": [{"url": "http://localhost/a"}, {"secret": "s"}],
+            "hooks": {"command": "true"}
+        }"#;
+        let mut conf = test_config("invalid-fields", Some(text));
+        load(&mut conf);
+
+        assert_eq!(conf.ui.locale, "en");
+        assert_eq!(conf.ui.timeline, UI::default().timeline);
+        assert_eq!(conf.feed.refresh_interval_mins, 5);
+        assert!(conf.feed.muted_keywords.is_empty());
+        assert_eq!(conf.webhooks.len(), 1);
+        assert!(conf.hooks.is_empty());
+
+        match &conf.warnings[..] {
+            [ConfigWarning::InvalidFields { fields }] => assert_eq!(
+                fields,
+                &[
+                    "ui.timeline",
+                    "feed.muted_keywords",
+                    "network",
+                    "webhooks[1]",
+                    "hooks"
+                ]
+            ),
+            warnings => panic!("{warnings:?}"),
+        }
+    }
+
+    #[test]
+    fn newer_version_is_read_only() {
+        let text = br#"{"version": 99, "future": true, "ui": {"locale": "en"}}"#;
+        let mut conf = test_config("newer", Some(text));
+        conf.load().unwrap();
+
+        assert!(conf.is_read_only);
+        assert_eq!(conf.ui.locale, "en");
+        assert!(matches!(
+            conf.warnings[..],
+            [ConfigWarning::Newer { version: 99 }]
+        ));
+        assert!(conf.save().unwrap_err().is::<ConfigReadOnly>());
+        assert_eq!(fs::read(&conf.config_path).unwrap(), text);
+    }
+
+    #[test]
+    fn truncated_file_with_backup() {
+        let mut conf = test_config("truncated-backup", None);
+        conf.version = CONFIG_VERSION;
+        conf.ui.locale = "en".to_string();
+        conf.save().unwrap();
+        conf.ui.locale = "zh-CN".to_string();
+        conf.save().unwrap();
+        fs::write(&conf.config_path, br#"{"ui": {"locale": "zh"#).unwrap();
+
+        let mut conf = Config {
+            config_path: conf.config_path.clone(),
+            ..Default::default()
+        };
+        load(&mut conf);
+
+        assert_eq!(conf.ui.locale, "en");
+        assert!(conf.warnings.is_empty());
+        assert!(store::take_corrupted_of(&conf.config_path).is_some_and(|c| c.is_restored));
+    }
+
+    #[test]
+    fn truncated_file_without_backup() {
+        let mut conf = test_config("truncated", Some(b"{\"ui\": {\"locale\": \"en\xe6"));
+        let saved = load(&mut conf);
+
+        assert_eq!(conf.ui.locale, UI::default().locale);
+        assert_eq!(saved["version"], CONFIG_VERSION);
+        assert!(store::with_suffix(&conf.config_path, "corrupt").exists());
+        assert!(matches!(&conf.warnings[..], [ConfigWarning::Corrupted(c)] if !c.is_restored));
+    }
+
+    #[test]
+    fn unreadable_file_is_not_overwritten() {
+        let mut conf = test_config("unreadable", None);
+        fs::create_dir(&conf.config_path).unwrap();
+        conf.load().unwrap();
+
+        assert!(conf.is_read_only);
+        assert!(matches!(
+            conf.warnings[..],
+            [ConfigWarning::Unreadable { .. }]
+        ));
+        assert!(conf.config_path.is_dir());
+    }
+}
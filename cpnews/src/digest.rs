@@ -0,0 +1,398 @@
+use super::{
+    app::{App, CurrentPanel, MsgType},
+    archive,
+    config::Config,
+    export::escape_html,
+    news::{Language, NewsItem},
+    store, theme,
+    tr::{tr, tr_args},
+    zh,
+};
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, Timelike};
+use egui::{CollapsingHeader, FontId, RichText, ScrollArea, Ui};
+use fluent_bundle::FluentArgs;
+use std::{fs, path::PathBuf};
+
+const OTHER_TOPIC: &str = "Other";
+
+// The first matched topic wins, so the more specific topics go first
+const TOPICS: &[(&str, &[&str])] = &[
+    ("BTC", &["btc", "bitcoin", "比特币"]),
+    ("ETH", &["eth", "ether", "ethereum", "以太坊"]),
+    ("SOL", &["sol", "solana"]),
+    ("XRP", &["xrp", "ripple", "瑞波"]),
+    ("BNB", &["bnb", "binance", "币安"]),
+    ("DOGE", &["doge", "dogecoin", "狗狗币"]),
+    ("TON", &["ton", "toncoin", "telegram"]),
+    ("ADA", &["ada", "cardano"]),
+    (
+        "Stablecoin",
+        &["usdt", "usdc", "tether", "stablecoin", "稳定币"],
+    ),
+    ("ETF", &["etf"]),
+    ("DeFi", &["defi", "dex", "uniswap", "aave"]),
+    ("NFT", &["nft", "nfts"]),
+    ("Regulation", &["sec", "regulation", "regulator", "监管"]),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Group {
+    pub topic: String,
+    pub count: usize,
+    pub items: Vec<NewsItem>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Section {
+    pub name: String,
+    pub count: usize,
+    pub groups: Vec<Group>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DailyDigest {
+    pub date: String,
+    pub sections: Vec<Section>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DigestPanel {
+    pub date: String,
+    pub digest: Option<DailyDigest>,
+}
+
+// Match ascii keywords as whole words, so that `eth` is not found in `method`
+fn contains_keyword(text: &str, keyword: &str) -> bool {
+    if !keyword.is_ascii() {
+        return text.contains(keyword);
+    }
+
+    text.match_indices(keyword).any(|(index, _)| {
+        let before = text[..index].chars().next_back();
+        let after = text[index + keyword.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric())
+            && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+pub fn topic(item: &NewsItem) -> &'static str {
+    let title = item.title.to_lowercase();
+
+    TOPICS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|kw| contains_keyword(&title, kw)))
+        .map(|(topic, _)| *topic)
+        .unwrap_or(OTHER_TOPIC)
+}
+
+impl DailyDigest {
+    pub fn build(date: &str, feeds: Vec<(&str, Vec<NewsItem>)>, top: usize) -> Self {
+        let sections = feeds
+            .into_iter()
+            .map(|(name, items)| {
+                let items = items
+                    .into_iter()
+                    .filter(|item| item.local_date() == date)
+                    .collect::<Vec<_>>();
+
+                let mut groups: Vec<Group> = vec![];
+                for item in items.iter() {
+                    let topic = topic(item);
+                    let index = match groups.iter().position(|g| g.topic == topic) {
+                        Some(index) => index,
+                        _ => {
+                            groups.push(Group {
+                                topic: topic.to_string(),
+                                ..Default::default()
+                            });
+                            groups.len() - 1
+                        }
+                    };
+
+                    let group = &mut groups[index];
+                    group.count += 1;
+                    if group.items.len() < top {
+                        group.items.push(item.clone());
+                    }
+                }
+
+                groups.sort_by_key(|g| (g.topic == OTHER_TOPIC, std::cmp::Reverse(g.count)));
+
+                Section {
+                    name: name.to_string(),
+                    count: items.len(),
+                    groups,
+                }
+            })
+            .collect();
+
+        Self {
+            date: date.to_string(),
+            sections,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(|s| s.count == 0)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("# cpnews digest {}\n", self.date);
+
+        for section in self.sections.iter() {
+            text.push_str(&format!("\n## {} ({})\n", section.name, section.count));
+
+            for group in section.groups.iter() {
+                text.push_str(&format!("\n### {} ({})\n\n", group.topic, group.count));
+
+                for item in group.items.iter() {
+                    text.push_str(&format!(
+                        "- [{}]({}) {}\n",
+                        item.title.replace('[', "\\[").replace(']', "\\]"),
+                        item.link,
+                        item.date
+                    ));
+                }
+            }
+        }
+
+        text
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = String::default();
+
+        for section in self.sections.iter() {
+            body.push_str(&format!(
+                "<h2>{} ({})</h2>\n",
+                escape_html(&section.name),
+                section.count
+            ));
+
+            for group in section.groups.iter() {
+                body.push_str(&format!(
+                    "<h3>{} ({})</h3>\n<ul>\n",
+                    escape_html(&group.topic),
+                    group.count
+                ));
+
+                for item in group.items.iter() {
+                    body.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a> <span>{}</span></li>\n",
+                        escape_html(&item.link),
+                        escape_html(&item.title),
+                        escape_html(&item.date)
+                    ));
+                }
+                body.push_str("</ul>\n");
+            }
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>cpnews digest {date}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+li {{ margin: 4px 0; }}
+span {{ color: gray; }}
+</style>
+</head>
+<body>
+<h1>cpnews digest {date}</h1>
+{body}</body>
+</html>
+"#,
+            date = escape_html(&self.date)
+        )
+    }
+}
+
+pub fn yesterday() -> String {
+    (Local::now() - Duration::days(1))
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+pub fn dir(conf: &Config) -> PathBuf {
+    conf.data_dir.join("digest")
+}
+
+pub fn build(conf: &Config, date: &str) -> DailyDigest {
+    let feeds = Language::ALL
+        .into_iter()
+        .map(|language| {
+            (
+                language.source().name(),
+                archive::load(&archive::path(&conf.data_dir, language)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    DailyDigest::build(date, feeds, conf.digest.top)
+}
+
+// Write the Markdown and HTML digest of the date, and return the Markdown file path
+pub fn generate(conf: &Config, date: &str) -> Result<PathBuf> {
+    let digest = build(conf, date);
+    let dir = dir(conf);
+    fs::create_dir_all(&dir)?;
+
+    let md_path = dir.join(format!("{date}.md"));
+    store::write(&md_path, digest.to_markdown())?;
+    store::write(&dir.join(format!("{date}.html")), digest.to_html())?;
+
+    Ok(md_path)
+}
+
+// Generate the digest of the previous day once it is past the scheduled hour
+pub fn generate_if_due(conf: &Config) -> Result<()> {
+    if !conf.digest.enabled || Local::now().hour() < conf.digest.hour {
+        return Ok(());
+    }
+
+    let date = yesterday();
+    if dir(conf).join(format!("{date}.md")).exists() {
+        return Ok(());
+    }
+
+    generate(conf, &date)?;
+    Ok(())
+}
+
+// The time until the next scheduled hour of the digest
+pub fn until_schedule(conf: &Config) -> std::time::Duration {
+    let now = Local::now();
+    let mut schedule = now.date_naive().and_hms_opt(conf.digest.hour, 0, 0);
+    if schedule.is_some_and(|schedule| schedule <= now.naive_local()) {
+        schedule = schedule.map(|schedule| schedule + Duration::days(1));
+    }
+
+    schedule
+        .and_then(|schedule| (schedule - now.naive_local()).to_std().ok())
+        .unwrap_or(std::time::Duration::from_secs(3600))
+}
+
+pub fn open(app: &mut App) {
+    if app.digest_panel.date.is_empty() {
+        app.digest_panel.date = yesterday();
+    }
+
+    app.digest_panel.digest = Some(build(&app.conf, &app.digest_panel.date));
+    app.current_panel = CurrentPanel::Digest;
+}
+
+fn shift_date(date: &str, days: i64) -> String {
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(d) => (d + Duration::days(days)).format("%Y-%m-%d").to_string(),
+        _ => yesterday(),
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    if ui
+        .add(
+            theme::image_and_text_button(
+                &app.back_icon,
+                theme::BACK_ICON_SIZE,
+                RichText::new(tr("daily-digest"))
+                    .font(FontId::proportional(theme::current().news_title_font_size)),
+            )
+            .frame(false),
+        )
+        .clicked()
+    {
+        app.current_panel = CurrentPanel::News;
+    }
+
+    ui.add_space(theme::current().spacing);
+
+    let mut days = 0;
+    ui.horizontal(|ui| {
+        if ui.button("◀").clicked() {
+            days = -1;
+        }
+
+        ui.label(&app.digest_panel.date);
+
+        if ui.button("▶").clicked() {
+            days = 1;
+        }
+
+        ui.add_space(theme::current().spacing * 2.);
+
+        if ui.button(tr("save")).clicked() {
+            match generate(&app.conf, &app.digest_panel.date) {
+                Ok(path) => app.show_message(
+                    tr_args(
+                        "saved-to",
+                        Some(&FluentArgs::from_iter([(
+                            "path",
+                            path.display().to_string(),
+                        )])),
+                    ),
+                    MsgType::Success,
+                ),
+                Err(e) => app.show_message(
+                    tr_args(
+                        "save-failed",
+                        Some(&FluentArgs::from_iter([("reason", format!("{e:?}"))])),
+                    ),
+                    MsgType::Warn,
+                ),
+            }
+        }
+    });
+
+    if days != 0 {
+        app.digest_panel.date = shift_date(&app.digest_panel.date, days);
+        app.digest_panel.digest = Some(build(&app.conf, &app.digest_panel.date));
+    }
+
+    ui.add_space(theme::current().spacing);
+
+    let digest = match app.digest_panel.digest.as_ref() {
+        Some(digest) if !digest.is_empty() => digest,
+        _ => {
+            ui.vertical_centered(|ui| {
+                ui.add_space(theme::current().spacing * 4.);
+                ui.label(RichText::new(tr("news-empty")).color(theme::current().light_color));
+            });
+            return;
+        }
+    };
+
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for section in digest.sections.iter() {
+                ui.heading(
+                    RichText::new(format!("{} ({})", section.name, section.count))
+                        .color(theme::current().brand_color),
+                );
+
+                for group in section.groups.iter() {
+                    CollapsingHeader::new(format!("{} ({})", group.topic, group.count))
+                        .id_source(format!("{}-{}", section.name, group.topic))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for item in group.items.iter() {
+                                ui.hyperlink_to(
+                                    RichText::new(zh::convert(
+                                        &item.title,
+                                        app.conf.ui.chinese_script,
+                                    ))
+                                    .color(theme::current().news_title_color),
+                                    &item.link,
+                                );
+                            }
+                        });
+                }
+
+                ui.add_space(theme::current().spacing * 2.);
+            }
+        });
+}
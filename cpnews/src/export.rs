@@ -0,0 +1,329 @@
+use super::{
+    app::{App, MsgType},
+    config::Config,
+    news::NewsItem,
+    theme,
+    tr::{tr, tr_args},
+};
+use anyhow::Result;
+use chrono::Local;
+use egui::{Context, RichText, TextEdit, Window};
+use fluent_bundle::FluentArgs;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Csv,
+    Json,
+    Html,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Markdown, Format::Csv, Format::Json, Format::Html];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Markdown => "Markdown",
+            Format::Csv => "CSV",
+            Format::Json => "JSON",
+            Format::Html => "HTML",
+        }
+    }
+
+    pub fn ext(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Html => "html",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scope {
+    #[default]
+    Current,
+    Selected,
+    Bookmarks,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ExportPanel {
+    pub is_open: bool,
+    pub scope: Scope,
+    pub format: Format,
+    pub path: String,
+
+    // keys of the items picked by the user when exporting a selection
+    pub selected: HashSet<String>,
+}
+
+impl ExportPanel {
+    pub fn is_selecting(&self) -> bool {
+        self.is_open && self.scope == Scope::Selected
+    }
+
+    pub fn toggle_selected(&mut self, item: &NewsItem) {
+        if !self.selected.remove(item.key()) {
+            self.selected.insert(item.key().to_string());
+        }
+    }
+}
+
+pub fn to_string(items: &[NewsItem], format: Format) -> Result<String> {
+    Ok(match format {
+        Format::Markdown => to_markdown(items),
+        Format::Csv => to_csv(items),
+        Format::Json => serde_json::to_string_pretty(items)?,
+        Format::Html => to_html(items),
+    })
+}
+
+pub fn export(items: &[NewsItem], format: Format, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    Ok(fs::write(path, to_string(items, format)?)?)
+}
+
+pub fn default_path(conf: &Config, format: Format) -> PathBuf {
+    let file_name = format!(
+        "cpnews-{}.{}",
+        Local::now().format("%Y%m%d-%H%M"),
+        format.ext()
+    );
+
+    #[cfg(not(target_os = "android"))]
+    if let Some(dirs) = platform_dirs::UserDirs::new() {
+        return dirs.download_dir.join(file_name);
+    }
+
+    conf.data_dir.join("export").join(file_name)
+}
+
+fn to_markdown(items: &[NewsItem]) -> String {
+    let mut text = String::from("# cpnews\n");
+
+    for item in items.iter() {
+        text.push_str(&format!(
+            "\n## [{}]({})\n\n{}\n\n*{} · {}*\n",
+            item.title.replace('[', "\\[").replace(']', "\\]"),
+            item.link,
+            item.summary,
+            item.date,
+            item.source
+        ));
+    }
+
+    text
+}
+
+fn to_csv(items: &[NewsItem]) -> String {
+    fn field(v: &str) -> String {
+        if v.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", v.replace('"', "\"\""))
+        } else {
+            v.to_string()
+        }
+    }
+
+    let mut text = String::from("title,summary,date,source,link\r\n");
+    for item in items.iter() {
+        let row = [
+            &item.title,
+            &item.summary,
+            &item.date,
+            &item.source,
+            &item.link,
+        ]
+        .map(|v| field(v))
+        .join(",");
+
+        text.push_str(&row);
+        text.push_str("\r\n");
+    }
+
+    text
+}
+
+pub fn escape_html(v: &str) -> String {
+    v.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn to_html(items: &[NewsItem]) -> String {
+    let mut rows = String::default();
+    for item in items.iter() {
+        rows.push_str(&format!(
+            r#"<tr><td><a href="{}">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>
+"#,
+            escape_html(&item.link),
+            escape_html(&item.title),
+            escape_html(&item.summary),
+            escape_html(&item.date),
+            escape_html(&item.source),
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>cpnews</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 6px; vertical-align: top; }}
+td:nth-child(3), td:nth-child(4) {{ white-space: nowrap; color: gray; }}
+</style>
+</head>
+<body>
+<h1>cpnews</h1>
+<table>
+<tr><th>title</th><th>summary</th><th>date</th><th>source</th></tr>
+{rows}</table>
+</body>
+</html>
+"#
+    )
+}
+
+fn items_of_scope(app: &App, scope: Scope) -> Vec<NewsItem> {
+    let news_items = app
+        .current_news_items()
+        .into_iter()
+        .map(|(_, item)| item.clone())
+        .collect::<Vec<_>>();
+
+    match scope {
+        Scope::Current => news_items,
+        Scope::Bookmarks => app.bookmarks.items.iter().map(|b| b.item.clone()).collect(),
+        Scope::Selected => {
+            let mut keys = HashSet::new();
+            news_items
+                .iter()
+                .chain(app.bookmarks.items.iter().map(|b| &b.item))
+                .filter(|item| {
+                    app.export_panel.selected.contains(item.key())
+                        && keys.insert(item.key().to_string())
+                })
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+pub fn open(app: &mut App) {
+    app.export_panel.is_open = true;
+    app.export_panel.path = default_path(&app.conf, app.export_panel.format)
+        .to_string_lossy()
+        .to_string();
+}
+
+pub fn ui(app: &mut App, ctx: &Context) {
+    if !app.export_panel.is_open {
+        return;
+    }
+
+    let mut is_open = true;
+    let mut is_export = false;
+
+    Window::new(tr("export"))
+        .open(&mut is_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let panel = &mut app.export_panel;
+
+            ui.horizontal_wrapped(|ui| {
+                ui.radio_value(&mut panel.scope, Scope::Current, tr("export-scope-current"));
+                ui.radio_value(
+                    &mut panel.scope,
+                    Scope::Selected,
+                    tr_args(
+                        "export-scope-selected",
+                        Some(&FluentArgs::from_iter([("count", panel.selected.len())])),
+                    ),
+                );
+                ui.radio_value(&mut panel.scope, Scope::Bookmarks, tr("bookmarks"));
+            });
+
+            ui.add_space(theme::current().spacing);
+
+            ui.horizontal_wrapped(|ui| {
+                for format in Format::ALL {
+                    if ui
+                        .radio_value(&mut panel.format, format, format.name())
+                        .changed()
+                    {
+                        panel.path = PathBuf::from(&panel.path)
+                            .with_extension(format.ext())
+                            .to_string_lossy()
+                            .to_string();
+                    }
+                }
+            });
+
+            ui.add_space(theme::current().spacing);
+
+            if cfg!(target_os = "android") {
+                ui.label(RichText::new(&panel.path).color(theme::current().light_color));
+            } else {
+                ui.add(TextEdit::singleline(&mut panel.path).desired_width(f32::INFINITY));
+            }
+
+            ui.add_space(theme::current().spacing);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("export")).clicked() {
+                    is_export = true;
+                }
+
+                if panel.scope == Scope::Selected && ui.button(tr("clear")).clicked() {
+                    panel.selected.clear();
+                }
+            });
+        });
+
+    if is_export {
+        let items = items_of_scope(app, app.export_panel.scope);
+        let path = PathBuf::from(&app.export_panel.path);
+
+        match export(&items, app.export_panel.format, &path) {
+            Err(e) => app.show_message(
+                tr_args(
+                    "export-failed",
+                    Some(&FluentArgs::from_iter([("reason", format!("{e:?}"))])),
+                ),
+                MsgType::Warn,
+            ),
+            Ok(_) => {
+                app.show_message(
+                    tr_args(
+                        "exported-to",
+                        Some(&FluentArgs::from_iter([(
+                            "path",
+                            path.display().to_string(),
+                        )])),
+                    ),
+                    MsgType::Success,
+                );
+                is_open = false;
+            }
+        }
+    }
+
+    app.export_panel.is_open = is_open;
+}
@@ -0,0 +1,296 @@
+use super::{config::Font, store};
+use egui::{Context, FontData, FontDefinitions, FontFamily};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::UNIX_EPOCH,
+};
+
+const EMBEDDED_FONT: &str = "SourceHanSerifCN";
+const CJK_FONT: &str = "system-cjk";
+const PROPORTIONAL_FONT: &str = "user-proportional";
+const MONOSPACE_FONT: &str = "user-monospace";
+
+const FONT_EXTS: [&str; 3] = ["ttf", "otf", "ttc"];
+
+// The Chinese fonts shipped with the systems, the embedded font is only used without any of them
+const SYSTEM_CJK_FONTS: [&str; 14] = [
+    "NotoSansCJK-Regular",
+    "NotoSansCJKsc-Regular",
+    "NotoSerifCJK-Regular",
+    "NotoSansSC-Regular",
+    "SourceHanSansSC-Regular",
+    "SourceHanSerifCN-Regular",
+    "DroidSansFallbackFull",
+    "DroidSansFallback",
+    "wqy-microhei",
+    "wqy-zenhei",
+    "msyh",
+    "simsun",
+    "PingFang",
+    "Hiragino Sans GB",
+];
+
+// The scan of the font directories is cached, and done again once the directories change
+const FONT_LIST_FILE: &str = "fonts.json";
+
+static SYSTEM_FONTS: OnceLock<Vec<(String, PathBuf)>> = OnceLock::new();
+static FONT_LIST_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+struct FontList {
+    // the font directories and their sub directories with their modified times
+    stamps: Vec<(PathBuf, u64)>,
+    fonts: Vec<(String, PathBuf)>,
+}
+
+// Counts the font changes, so the text measured with the previous fonts is measured again
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+
+    if cfg!(target_os = "android") {
+        dirs.push(PathBuf::from("/system/fonts"));
+    } else if cfg!(target_os = "windows") {
+        let root = std::env::var("SystemRoot").unwrap_or("C:\\Windows".to_string());
+        dirs.push(Path::new(&root).join("Fonts"));
+        if let Ok(dir) = std::env::var("LOCALAPPDATA") {
+            dirs.push(Path::new(&dir).join("Microsoft\\Windows\\Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        if let Ok(home) = std::env::var("HOME") {
+            dirs.push(Path::new(&home).join("Library/Fonts"));
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Ok(home) = std::env::var("HOME") {
+            dirs.push(Path::new(&home).join(".local/share/fonts"));
+            dirs.push(Path::new(&home).join(".fonts"));
+        }
+    }
+
+    dirs
+}
+
+fn scan_dir(dir: &Path, fonts: &mut Vec<(String, PathBuf)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        _ => return,
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            scan_dir(&path, fonts);
+            continue;
+        }
+
+        let is_font = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FONT_EXTS.contains(&ext.to_lowercase().as_str()));
+
+        if let (true, Some(name)) = (is_font, path.file_stem()) {
+            fonts.push((name.to_string_lossy().to_string(), path));
+        }
+    }
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+// A font installed or removed changes the modified time of its directory or of the parent
+// directory, e.g. `/usr/share/fonts/truetype` for `/usr/share/fonts/truetype/noto`
+fn dir_stamps() -> Vec<(PathBuf, u64)> {
+    let mut stamps = vec![];
+    for dir in font_dirs() {
+        stamps.push((dir.clone(), modified_secs(&dir)));
+
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    stamps.push((path.clone(), modified_secs(&path)));
+                }
+            }
+        }
+    }
+    stamps
+}
+
+fn scan_fonts() -> Vec<(String, PathBuf)> {
+    let mut fonts = vec![];
+    for dir in font_dirs() {
+        scan_dir(&dir, &mut fonts);
+    }
+
+    fonts.sort_by_key(|(name, _)| name.to_lowercase());
+    fonts.dedup_by(|a, b| a.0 == b.0);
+    fonts
+}
+
+// The fonts of the system font directories by their file names, read from the cached scan while
+// the directories are unchanged
+pub fn system_fonts() -> &'static Vec<(String, PathBuf)> {
+    SYSTEM_FONTS.get_or_init(|| {
+        let path = match FONT_LIST_PATH.get() {
+            Some(path) => path,
+            _ => return scan_fonts(),
+        };
+
+        let stamps = dir_stamps();
+        if let Some(list) = store::load::<FontList>(path) {
+            if list.stamps == stamps {
+                return list.fonts;
+            }
+        }
+
+        let list = FontList {
+            stamps,
+            fonts: scan_fonts(),
+        };
+        match serde_json::to_string(&list) {
+            Ok(text) => {
+                if let Err(e) = store::write(path, text) {
+                    log::warn!("{e:?}");
+                }
+            }
+            Err(e) => log::warn!("{e:?}"),
+        }
+        list.fonts
+    })
+}
+
+// The font is a file path or the name of a system font
+fn read_font(font: &str) -> Option<FontData> {
+    let path = if Path::new(font).is_file() {
+        PathBuf::from(font)
+    } else {
+        system_fonts()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(font))
+            .map(|(_, path)| path.clone())?
+    };
+
+    // the first font of a font collection is used. egui panics on the data it can not parse, so
+    // a file which is not a font is skipped
+    match fs::read(&path) {
+        Ok(data) => match ab_glyph::FontRef::try_from_slice(&data) {
+            Ok(_) => Some(FontData::from_owned(data)),
+            Err(e) => {
+                log::warn!("{}: {e:?}", path.display());
+                None
+            }
+        },
+        Err(e) => {
+            log::warn!("{}: {e:?}", path.display());
+            None
+        }
+    }
+}
+
+// The first Chinese font of the system, or else the embedded font
+fn cjk_font() -> (&'static str, FontData) {
+    for font in SYSTEM_CJK_FONTS {
+        if let Some(data) = read_font(font) {
+            return (CJK_FONT, data);
+        }
+    }
+
+    (
+        EMBEDDED_FONT,
+        FontData::from_static(include_bytes!("./res/font/SourceHanSerifCN.ttf")),
+    )
+}
+
+fn set_fonts(ctx: &Context, fonts: FontDefinitions) {
+    ctx.set_fonts(fonts);
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn generation() -> usize {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+// The fonts of egui until the fonts are loaded
+pub fn init(ctx: &Context) {
+    set_fonts(ctx, FontDefinitions::default());
+}
+
+// The chosen fonts go first, the Chinese font and the emoji fonts of egui are kept as fallbacks
+fn build(conf: &Font) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+
+    let (name, data) = cjk_font();
+    fonts.font_data.insert(name.to_owned(), data);
+    fonts
+        .families
+        .get_mut(&FontFamily::Proportional)
+        .unwrap()
+        .insert(0, name.to_owned());
+
+    for (name, font, family) in [
+        (
+            PROPORTIONAL_FONT,
+            &conf.proportional,
+            FontFamily::Proportional,
+        ),
+        (MONOSPACE_FONT, &conf.monospace, FontFamily::Monospace),
+    ] {
+        if font.is_empty() {
+            continue;
+        }
+
+        if let Some(data) = read_font(font) {
+            fonts.font_data.insert(name.to_owned(), data);
+            fonts
+                .families
+                .get_mut(&family)
+                .unwrap()
+                .insert(0, name.to_owned());
+        }
+    }
+
+    for font in conf.fallbacks.iter() {
+        let data = match read_font(font) {
+            Some(data) => data,
+            _ => {
+                log::debug!("fallback font `{font}` is not found");
+                continue;
+            }
+        };
+
+        let name = format!("fallback-{font}");
+        fonts.font_data.insert(name.clone(), data);
+        for family in [FontFamily::Proportional, FontFamily::Monospace] {
+            fonts.families.get_mut(&family).unwrap().push(name.clone());
+        }
+    }
+
+    fonts
+}
+
+// Read the fonts in the background, so the startup does not wait for the font files. The scan
+// of the font directories is cached in `cache_dir`
+pub fn load(ctx: &Context, conf: &Font, cache_dir: &Path) {
+    let _ = FONT_LIST_PATH.set(cache_dir.join(FONT_LIST_FILE));
+
+    let ctx = ctx.clone();
+    let conf = conf.clone();
+    thread::spawn(move || {
+        set_fonts(&ctx, build(&conf));
+        ctx.request_repaint();
+    });
+}
@@ -0,0 +1,144 @@
+use super::{
+    config::{Config, Hook, HookMode},
+    news::{NewsItem, Source},
+    worker::Worker,
+};
+use anyhow::{anyhow, Result};
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// The hooks run one by one on the runner rather than in the fetching threads
+static RUNNER: Worker = Worker::new("hook", Duration::MAX, || ());
+
+fn shell(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+// Run the command with the payload on stdin, and kill it once the timeout is reached
+fn exec(hook: &Hook, envs: Vec<(&str, String)>, stdin: String) -> Result<()> {
+    let mut child = shell(&hook.command)
+        .envs(envs)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // write and read in threads, so that a command ignoring its stdin can not block us
+    let mut child_stdin = child.stdin.take().unwrap();
+    thread::spawn(move || {
+        let _ = child_stdin.write_all(stdin.as_bytes());
+    });
+
+    let mut child_stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut text = String::default();
+        let _ = child_stderr.read_to_string(&mut text);
+        text
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(hook.timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!(
+                "`{}` timed out after {}s",
+                hook.command,
+                hook.timeout_secs
+            ));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    if !status.success() {
+        return Err(anyhow!(
+            "`{}` exited with {status}: {}",
+            hook.command,
+            stderr.join().unwrap_or_default().trim()
+        ));
+    }
+
+    Ok(())
+}
+
+fn item_envs(source: Source, item: &NewsItem) -> Vec<(&'static str, String)> {
+    vec![
+        ("CPNEWS_FEED", source.name().to_string()),
+        ("CPNEWS_TITLE", item.title.clone()),
+        ("CPNEWS_SUMMARY", item.summary.clone()),
+        ("CPNEWS_DATE", item.date.clone()),
+        ("CPNEWS_LINK", item.link.clone()),
+        ("CPNEWS_SOURCE", item.source.clone()),
+    ]
+}
+
+// Run the hooks for the new items in the background, so the slow hooks do not hold up the news
+pub fn run(conf: &Config, source: Source, items: &[NewsItem]) {
+    if items.is_empty() || conf.hooks.is_empty() {
+        return;
+    }
+
+    let hooks = conf.hooks.clone();
+    let items = items.to_vec();
+    RUNNER.run(move || run_hooks(&hooks, source, &items));
+}
+
+// Wait for the hooks, e.g. before a command line process exits
+pub fn wait() {
+    RUNNER.wait();
+}
+
+// Errors are logged and do not stop the other hooks
+fn run_hooks(hooks: &[Hook], source: Source, items: &[NewsItem]) {
+    for hook in hooks.iter().filter(|h| !h.command.is_empty()) {
+        let result = match hook.mode {
+            HookMode::Item => {
+                for item in items.iter() {
+                    let result = serde_json::to_string(item)
+                        .map_err(|e| anyhow!("{e:?}"))
+                        .and_then(|stdin| exec(hook, item_envs(source, item), stdin));
+
+                    if let Err(e) = result {
+                        log::warn!("hook: {e}");
+                    }
+                }
+                Ok(())
+            }
+            HookMode::Batch => serde_json::to_string(items)
+                .map_err(|e| anyhow!("{e:?}"))
+                .and_then(|stdin| {
+                    exec(
+                        hook,
+                        vec![
+                            ("CPNEWS_FEED", source.name().to_string()),
+                            ("CPNEWS_COUNT", items.len().to_string()),
+                        ],
+                        stdin,
+                    )
+                }),
+        };
+
+        if let Err(e) = result {
+            log::warn!("hook: {e}");
+        }
+    }
+}
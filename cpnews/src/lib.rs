@@ -7,30 +7,60 @@ use egui_wgpu::wgpu;
 use egui_wgpu::winit::Painter;
 use egui_winit::winit::{self, window::Icon};
 use egui_winit::State;
+use std::time::{Duration, Instant};
 use winit::event::Event::*;
+use winit::event::StartCause;
 use winit::event_loop::ControlFlow;
 use winit::event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget};
 
 #[cfg(target_os = "android")]
 use crate::winit::platform::android::activity::AndroidApp;
 
+#[cfg(not(target_os = "android"))]
+use clap::Parser;
+
 mod about;
 mod app;
+mod archive;
+mod bookmark;
 mod config;
+mod digest;
+mod export;
+mod font;
+mod hook;
+mod list;
 mod news;
+mod read_state;
+mod settings;
+mod store;
 mod theme;
 mod tr;
+mod translate;
 mod util;
 mod version;
+mod webhook;
+mod worker;
+mod zh;
+
+#[cfg(not(target_os = "android"))]
+mod cli;
+
+#[cfg(not(target_os = "android"))]
+mod server;
+
+#[cfg(not(target_os = "android"))]
+mod tui;
 
 use app::App;
+use config::Dirs;
 
 const INITIAL_WIDTH: u32 = 1920;
 const INITIAL_HEIGHT: u32 = 1080;
 
 // A custom event type for the winit app.
 enum Event {
-    RequestRedraw,
+    // repaint after the duration, at once if zero
+    RequestRedraw(Duration),
 }
 
 // Enable egui to request redraws via a custom Winit event...
@@ -54,7 +84,7 @@ fn create_window<T>(
                 icon.width() as u32,
                 icon.height() as u32,
             )
-            .map_or(None, |v| Some(v))
+            .ok()
         })
         .with_inner_size(winit::dpi::PhysicalSize {
             width: INITIAL_WIDTH,
@@ -80,19 +110,19 @@ fn create_window<T>(
     Some(window)
 }
 
-fn _main(event_loop: EventLoop<Event>) {
+fn _main(event_loop: EventLoop<Event>, dirs: Dirs) {
     let ctx = egui::Context::default();
     theme::init(&ctx);
 
     let repaint_signal = RepaintSignal(std::sync::Arc::new(std::sync::Mutex::new(
         event_loop.create_proxy(),
     )));
-    ctx.set_request_repaint_callback(move |_info| {
+    ctx.set_request_repaint_callback(move |info| {
         repaint_signal
             .0
             .lock()
             .unwrap()
-            .send_event(Event::RequestRedraw)
+            .send_event(Event::RequestRedraw(info.after))
             .ok();
     });
 
@@ -115,73 +145,104 @@ fn _main(event_loop: EventLoop<Event>) {
     );
     let mut window: Option<winit::window::Window> = None;
     let mut egui_windows = App::default();
-    egui_windows.init(&ctx);
+    egui_windows.init(&ctx, &dirs);
 
-    event_loop.run(move |event, event_loop, control_flow| match event {
-        Resumed => match window {
-            None => {
-                window = create_window(event_loop, &mut state, &mut painter);
-            }
-            Some(ref window) => {
-                pollster::block_on(painter.set_window(Some(window))).unwrap_or_else(|err| {
-                    log::error!(
-                        "Failed to associate window with painter after resume event: {err:?}"
-                    )
-                });
-                window.request_redraw();
-            }
-        },
-        Suspended => {
-            window = None;
-        }
-        RedrawRequested(..) => {
-            if let Some(window) = window.as_ref() {
-                let raw_input = state.take_egui_input(window);
-
-                let full_output = ctx.run(raw_input, |ctx| {
-                    egui_windows.ui(ctx);
-                });
-                state.handle_platform_output(window, &ctx, full_output.platform_output);
-
-                painter.paint_and_update_textures(
-                    state.pixels_per_point(),
-                    [0.0, 0.0, 0.0, 0.0],
-                    &ctx.tessellate(full_output.shapes),
-                    &full_output.textures_delta,
-                    false, // capture
-                );
-
-                if full_output.repaint_after.is_zero() {
+    // The frames are only painted on input, on the data of the background jobs and on the
+    // deadlines of egui, e.g. of an animation or a popup message, the loop sleeps otherwise
+    let mut repaint_at: Option<Instant> = None;
+
+    event_loop.run(move |event, event_loop, control_flow| {
+        match event {
+            NewEvents(StartCause::ResumeTimeReached { .. }) => {
+                repaint_at = None;
+                if let Some(window) = window.as_ref() {
                     window.request_redraw();
                 }
-            } else {
-                log::debug!("RedrawRequested, with no window set");
             }
-        }
-        MainEventsCleared | UserEvent(Event::RequestRedraw) => {
-            if let Some(window) = window.as_ref() {
-                window.request_redraw();
-            }
-        }
-        WindowEvent { event, .. } => {
-            match event {
-                winit::event::WindowEvent::Resized(size) => {
-                    painter.on_window_resized(size.width, size.height);
+            Resumed => match window {
+                None => {
+                    window = create_window(event_loop, &mut state, &mut painter);
                 }
-                winit::event::WindowEvent::CloseRequested => {
-                    *control_flow = ControlFlow::Exit;
+                Some(ref window) => {
+                    pollster::block_on(painter.set_window(Some(window))).unwrap_or_else(|err| {
+                        log::error!(
+                            "Failed to associate window with painter after resume event: {err:?}"
+                        )
+                    });
+                    window.request_redraw();
                 }
-                _ => {}
+            },
+            Suspended => {
+                egui_windows.flush();
+                window = None;
             }
+            RedrawRequested(..) => {
+                if let Some(window) = window.as_ref() {
+                    let raw_input = state.take_egui_input(window);
 
-            let response = state.on_event(&ctx, &event);
-            if response.repaint {
+                    let full_output = ctx.run(raw_input, |ctx| {
+                        egui_windows.ui(ctx);
+                    });
+                    state.handle_platform_output(window, &ctx, full_output.platform_output);
+
+                    painter.paint_and_update_textures(
+                        state.pixels_per_point(),
+                        [0.0, 0.0, 0.0, 0.0],
+                        &ctx.tessellate(full_output.shapes),
+                        &full_output.textures_delta,
+                        false, // capture
+                    );
+
+                    // `Duration::MAX` when egui has nothing to repaint
+                    repaint_at = if full_output.repaint_after.is_zero() {
+                        window.request_redraw();
+                        None
+                    } else {
+                        Instant::now().checked_add(full_output.repaint_after)
+                    };
+                } else {
+                    log::debug!("RedrawRequested, with no window set");
+                }
+            }
+            UserEvent(Event::RequestRedraw(after)) => {
                 if let Some(window) = window.as_ref() {
-                    window.request_redraw();
+                    if after.is_zero() {
+                        window.request_redraw();
+                    } else if let Some(instant) = Instant::now().checked_add(after) {
+                        repaint_at = Some(repaint_at.map_or(instant, |at| at.min(instant)));
+                    }
+                }
+            }
+            WindowEvent { event, .. } => {
+                match event {
+                    winit::event::WindowEvent::Resized(size) => {
+                        painter.on_window_resized(size.width, size.height);
+                    }
+                    winit::event::WindowEvent::CloseRequested => {
+                        egui_windows.flush();
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    winit::event::WindowEvent::ThemeChanged(_) => {
+                        egui_windows.on_system_theme_changed(&ctx);
+                    }
+                    _ => {}
+                }
+
+                let response = state.on_event(&ctx, &event);
+                if response.repaint {
+                    if let Some(window) = window.as_ref() {
+                        window.request_redraw();
+                    }
                 }
             }
+            _ => (),
+        }
+
+        // an exit is kept by winit
+        match repaint_at {
+            Some(instant) => control_flow.set_wait_until(instant),
+            None => control_flow.set_wait(),
         }
-        _ => (),
     });
 }
 
@@ -203,10 +264,15 @@ fn android_main(app: AndroidApp) {
             ),
     );
 
+    theme::SYSTEM_DARK.store(
+        app.config().ui_mode_night() == ndk::configuration::UiModeNight::Yes,
+        std::sync::atomic::Ordering::Relaxed,
+    );
+
     let event_loop = EventLoopBuilder::with_user_event()
         .with_android_app(app)
         .build();
-    _main(event_loop);
+    _main(event_loop, Dirs::default());
 }
 
 #[allow(dead_code)]
@@ -217,6 +283,21 @@ fn main() {
         .parse_default_env()
         .init();
 
+    let cli = cli::Cli::parse();
+    let dirs = cli.dirs();
+    if let Some(command) = cli.command {
+        let result = cli::run(command, &dirs);
+        for corrupted in store::take_corrupted() {
+            eprintln!("{}", corrupted.message());
+        }
+
+        if let Err(e) = result {
+            eprintln!("{e:?}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let event_loop = EventLoopBuilder::with_user_event().build();
-    _main(event_loop);
+    _main(event_loop, dirs);
 }
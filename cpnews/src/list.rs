@@ -0,0 +1,109 @@
+use super::font;
+use egui::{Rect, ScrollArea, Ui};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+// What the heights of the rows depend on besides their contents
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Layout {
+    width: f32,
+    pixels_per_point: f32,
+    font_generation: usize,
+
+    // the settings of the caller, e.g. the font scale
+    style: u64,
+}
+
+// A list of rows of different heights, which only lays out the visible rows. The heights are
+// measured when the rows are shown and cached by the row keys, so the new rows on top do not
+// shift them. The rows never shown are estimated by the average height of the measured ones
+#[derive(Clone, Debug, Default)]
+pub struct VirtualList {
+    heights: HashMap<String, f32>,
+    layout: Layout,
+}
+
+impl VirtualList {
+    // Show the rows of the keys through `add_row`, and return the range of the shown rows
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        sarea: ScrollArea,
+        keys: &[&str],
+        style: u64,
+        mut add_row: impl FnMut(&mut Ui, usize),
+    ) -> Range<usize> {
+        let layout = Layout {
+            width: ui.available_width(),
+            pixels_per_point: ui.ctx().pixels_per_point(),
+            font_generation: font::generation(),
+            style,
+        };
+        if layout != self.layout {
+            self.heights.clear();
+            self.layout = layout;
+        }
+
+        // drop the rows gone from the list, e.g. by the trimming of the news cache
+        if self.heights.len() > keys.len() * 2 {
+            let keys = keys.iter().collect::<HashSet<_>>();
+            self.heights.retain(|key, _| keys.contains(&key.as_str()));
+        }
+
+        let estimate = if self.heights.is_empty() {
+            ui.spacing().interact_size.y
+        } else {
+            self.heights.values().sum::<f32>() / self.heights.len() as f32
+        };
+
+        // the top of each row, and the height of the list at the end
+        let mut tops = Vec::with_capacity(keys.len() + 1);
+        let mut top = 0.0;
+        tops.push(top);
+        for key in keys {
+            top += self.heights.get(*key).copied().unwrap_or(estimate);
+            tops.push(top);
+        }
+
+        let heights = &mut self.heights;
+        sarea
+            .show_viewport(ui, |ui, viewport| {
+                ui.set_height(top);
+
+                if keys.is_empty() {
+                    return 0..0;
+                }
+
+                // the first row is the one partly scrolled past
+                let first = tops
+                    .partition_point(|top| *top <= viewport.min.y)
+                    .saturating_sub(1)
+                    .min(keys.len() - 1);
+                let bottom = ui.max_rect().top() + viewport.max.y;
+                let rect = Rect::from_x_y_ranges(
+                    ui.max_rect().x_range(),
+                    ui.max_rect().top() + tops[first]..=bottom,
+                );
+
+                ui.allocate_ui_at_rect(rect, |ui| {
+                    // make sure the rows get consistent IDs
+                    ui.skip_ahead_auto_ids(first);
+
+                    // the rows are shown until the viewport is filled, whatever the estimates
+                    let mut row = first;
+                    while row < keys.len() && ui.cursor().top() < bottom {
+                        let top = ui.cursor().top();
+                        add_row(ui, row);
+                        heights.insert(keys[row].to_string(), ui.cursor().top() - top);
+                        row += 1;
+                    }
+
+                    first..row
+                })
+                .inner
+            })
+            .inner
+    }
+}
@@ -1,7 +1,13 @@
-use super::util;
+use super::{archive, config::Config, hook, store, util, webhook, zh};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use serde_json::Value;
-use std::{fs, path::Path};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[cfg(target_os = "android")]
 const MAX_NEWS_ITEM: usize = 30;
@@ -13,8 +19,112 @@ const MAX_NEWS_ITEM: usize = 100;
 pub struct NewsItem {
     pub title: String,
     pub summary: String,
+
+    // in the time zone of the source, as shown to the user
     pub date: String,
     pub link: String,
+
+    #[serde(default)]
+    pub source: String,
+
+    // the publish time in UTC, which the news of both sources are ordered by
+    #[serde(default)]
+    pub timestamp: i64,
+}
+
+impl NewsItem {
+    // The link is unique per item, fall back to the title for items without one
+    pub fn key(&self) -> &str {
+        if self.link.is_empty() {
+            &self.title
+        } else {
+            &self.link
+        }
+    }
+
+    // The items cached before the timestamp was kept fall back to the date in the time zone
+    // of their source
+    pub fn published_at(&self) -> Option<DateTime<Utc>> {
+        let timestamp = if self.timestamp != 0 {
+            Some(self.timestamp)
+        } else if self.source == "odaily" {
+            util::timestamp_at(&self.date, util::ODAILY_UTC_OFFSET_SECS)
+        } else {
+            util::timestamp_at(&self.date, 0)
+        };
+
+        timestamp.and_then(|t| Utc.timestamp_opt(t, 0).single())
+    }
+
+    // The `%Y-%m-%d` date of the item in the local time zone, or else the date of its source
+    pub fn local_date(&self) -> String {
+        match self.published_at() {
+            Some(time) => time.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            _ => self.date.chars().take(10).collect(),
+        }
+    }
+}
+
+// Language of the news, each language is served by one source
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    Cn,
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Cn, Language::En];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Cn => "cn",
+            Language::En => "en",
+        }
+    }
+
+    pub fn source(&self) -> Source {
+        match self {
+            Language::Cn => Source::Odaily,
+            Language::En => Source::CryptoCompare,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    Odaily,
+    CryptoCompare,
+}
+
+impl Source {
+    pub const ALL: [Source; 2] = [Source::Odaily, Source::CryptoCompare];
+
+    pub fn language(&self) -> Language {
+        match self {
+            Source::Odaily => Language::Cn,
+            Source::CryptoCompare => Language::En,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Source::Odaily => "odaily",
+            Source::CryptoCompare => "cryptocompare",
+        }
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::ALL
+            .into_iter()
+            .find(|source| source.name().eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown source `{s}`"))
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -37,9 +147,84 @@ pub struct CryptoCompareNews {
     pub data: Vec<Value>,
 }
 
-pub fn fetch_cryptocompare(path: &Path) -> Result<Vec<NewsItem>> {
+// Fetch the news of the feed, the news cache and the archive are updated as well
+pub fn fetch(conf: &Config, source: Source) -> Result<Vec<NewsItem>> {
+    let language = source.language();
+    let cache_path = cache_path(&conf.cache_dir, language);
+
+    let cached_keys = load_items(&cache_path)
+        .iter()
+        .map(|item| item.key().to_string())
+        .collect::<HashSet<_>>();
+
+    let items = match source {
+        Source::Odaily => fetch_odaily(conf, &cache_path)?,
+        Source::CryptoCompare => fetch_cryptocompare(conf, &cache_path)?,
+    };
+
+    if let Err(e) = archive::update(
+        &archive::path(&conf.data_dir, language),
+        &items,
+        conf.archive.retention_days,
+    ) {
+        log::warn!("{e:?}");
+    }
+
+    // the first fetch only sets up the cache, or else the whole feed would be taken as new
+    let new_items = if cached_keys.is_empty() {
+        vec![]
+    } else {
+        items
+            .iter()
+            .filter(|item| !cached_keys.contains(item.key()))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    webhook::deliver(conf, source, &new_items);
+    hook::run(conf, source, &new_items);
+
+    Ok(items)
+}
+
+// Merge the feeds into one timeline, the newest news goes first
+pub fn timeline<'a>(
+    feeds: impl IntoIterator<Item = (Language, &'a [NewsItem])>,
+) -> Vec<(Language, &'a NewsItem)> {
+    let mut items = feeds
+        .into_iter()
+        .flat_map(|(language, items)| items.iter().map(move |item| (language, item)))
+        .collect::<Vec<_>>();
+
+    // the news without a valid date goes last
+    items.sort_by_cached_key(|(_, item)| Reverse(item.published_at()));
+    items
+}
+
+// All the words of the query should be found in the title or the summary
+pub fn matches(item: &NewsItem, query: &str) -> bool {
+    // both Simplified and Traditional Chinese queries match the news
+    let text = zh::to_simplified(&format!("{}\n{}", item.title, item.summary)).to_lowercase();
+    zh::to_simplified(query)
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
+
+// The news matching any of the muted keywords is hidden
+pub fn is_muted(item: &NewsItem, muted_keywords: &[String]) -> bool {
+    muted_keywords
+        .iter()
+        .any(|keyword| !keyword.trim().is_empty() && matches(item, keyword))
+}
+
+pub fn fetch_cryptocompare(conf: &Config, path: &Path) -> Result<Vec<NewsItem>> {
     const NEWS_API: &str = "https://min-api.cryptocompare.com/data/v2/news/?lang=EN";
-    let resp = reqwest::blocking::get(NEWS_API)?.json::<CryptoCompareNews>()?;
+    let resp = util::http_client(&conf.network)?
+        .build()?
+        .get(NEWS_API)
+        .send()?
+        .json::<CryptoCompareNews>()?;
 
     if resp.r#type != 100i32 {
         return Err(anyhow!("remove server error"));
@@ -78,16 +263,16 @@ pub fn fetch_cryptocompare(path: &Path) -> Result<Vec<NewsItem>> {
             _ => continue,
         };
 
-        let date = match item.get("published_on") {
+        let timestamp = match item.get("published_on") {
             Some(Value::Number(v)) => {
                 if !v.is_i64() {
                     continue;
                 }
-                let v = v.as_i64().unwrap();
-                util::time_from_utc_seconds(v)
+                v.as_i64().unwrap()
             }
             _ => continue,
         };
+        let date = util::time_from_utc_seconds(timestamp);
 
         let link = match item.get("url") {
             Some(Value::String(v)) => {
@@ -99,11 +284,18 @@ pub fn fetch_cryptocompare(path: &Path) -> Result<Vec<NewsItem>> {
             _ => continue,
         };
 
+        let source = match item.get("source_info").and_then(|v| v.get("name")) {
+            Some(Value::String(v)) if !v.is_empty() => v.clone(),
+            _ => "cryptocompare".to_string(),
+        };
+
         news_items.push(NewsItem {
             title,
             summary,
             date,
             link,
+            source,
+            timestamp,
         });
     }
 
@@ -114,9 +306,13 @@ pub fn fetch_cryptocompare(path: &Path) -> Result<Vec<NewsItem>> {
     Ok(news_items)
 }
 
-pub fn fetch_odaily(path: &Path) -> Result<Vec<NewsItem>> {
+pub fn fetch_odaily(conf: &Config, path: &Path) -> Result<Vec<NewsItem>> {
     const NEWS_API: &str = "https://www.odaily.news/v1/openapi/feeds";
-    let resp = reqwest::blocking::get(NEWS_API)?.json::<OdailyNews>()?;
+    let resp = util::http_client(&conf.network)?
+        .build()?
+        .get(NEWS_API)
+        .send()?
+        .json::<OdailyNews>()?;
 
     if resp.code != 0i32 {
         return Err(anyhow!("remove server error"));
@@ -181,8 +377,10 @@ pub fn fetch_odaily(path: &Path) -> Result<Vec<NewsItem>> {
         news_items.push(NewsItem {
             title,
             summary,
+            timestamp: util::timestamp_at(&date, util::ODAILY_UTC_OFFSET_SECS).unwrap_or_default(),
             date,
             link,
+            source: "odaily".to_string(),
         });
     }
 
@@ -193,23 +391,24 @@ pub fn fetch_odaily(path: &Path) -> Result<Vec<NewsItem>> {
     Ok(news_items)
 }
 
-pub fn load(cache_dir: &Path) -> (Vec<NewsItem>, Vec<NewsItem>) {
-    let cn_items = {
-        let text = fs::read_to_string(cache_dir.join("news-cn.json")).unwrap_or(String::default());
-        serde_json::from_str::<Vec<NewsItem>>(&text).unwrap_or(vec![])
-    };
+pub fn load_items(path: &Path) -> Vec<NewsItem> {
+    store::load::<Vec<NewsItem>>(path).unwrap_or_default()
+}
 
-    let en_items = {
-        let text = fs::read_to_string(cache_dir.join("news-en.json")).unwrap_or(String::default());
-        serde_json::from_str::<Vec<NewsItem>>(&text).unwrap_or(vec![])
-    };
+pub fn cache_path(cache_dir: &Path, language: Language) -> PathBuf {
+    cache_dir.join(format!("news-{}.json", language.code()))
+}
 
-    (cn_items, en_items)
+pub fn load(cache_dir: &Path) -> (Vec<NewsItem>, Vec<NewsItem>) {
+    (
+        load_items(&cache_path(cache_dir, Language::Cn)),
+        load_items(&cache_path(cache_dir, Language::En)),
+    )
 }
 
 fn save(path: &Path, items: &Vec<NewsItem>) -> Result<()> {
     match serde_json::to_string_pretty(items) {
-        Ok(text) => Ok(fs::write(path, text)?),
+        Ok(text) => store::write(path, text),
         Err(e) => Err(anyhow!("{e:?}")),
     }
 }
@@ -0,0 +1,114 @@
+use super::{
+    news::{Language, NewsItem},
+    store, util,
+};
+use anyhow::{anyhow, Result};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+#[cfg(target_os = "android")]
+const MAX_READ_ITEM: usize = 300;
+
+#[cfg(not(target_os = "android"))]
+const MAX_READ_ITEM: usize = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReadState {
+    #[serde(skip)]
+    path: PathBuf,
+
+    // item key -> timestamp of when it was read
+    #[serde(default)]
+    read: HashMap<String, i64>,
+
+    // key of the newest item seen in each feed
+    #[serde(default)]
+    last_seen_cn: String,
+
+    #[serde(default)]
+    last_seen_en: String,
+
+    // where the previous session left off, used to draw the "new since last visit" divider
+    #[serde(skip)]
+    pub marker_cn: String,
+
+    #[serde(skip)]
+    pub marker_en: String,
+}
+
+impl ReadState {
+    pub fn load(path: &Path) -> Self {
+        let mut state = store::load::<ReadState>(path).unwrap_or_default();
+
+        state.path = path.to_path_buf();
+        state.marker_cn = state.last_seen_cn.clone();
+        state.marker_en = state.last_seen_en.clone();
+        state
+    }
+
+    pub fn save(&self) -> Result<()> {
+        match serde_json::to_string_pretty(self) {
+            Ok(text) => store::write(&self.path, text),
+            Err(e) => Err(anyhow!("{e:?}")),
+        }
+    }
+
+    pub fn is_read(&self, item: &NewsItem) -> bool {
+        self.read.contains_key(item.key())
+    }
+
+    pub fn unread_count<'a>(&self, items: impl IntoIterator<Item = &'a NewsItem>) -> usize {
+        items.into_iter().filter(|item| !self.is_read(item)).count()
+    }
+
+    // Return true if any item is newly marked as read
+    pub fn mark_read<'a>(&mut self, items: impl IntoIterator<Item = &'a NewsItem>) -> bool {
+        let now = util::timestamp();
+        let mut is_changed = false;
+
+        for item in items {
+            if !self.is_read(item) {
+                self.read.insert(item.key().to_string(), now);
+                is_changed = true;
+            }
+        }
+
+        if is_changed && self.read.len() > MAX_READ_ITEM {
+            let mut timestamps = self.read.values().copied().collect::<Vec<_>>();
+            timestamps.sort_unstable();
+            let threshold = timestamps[self.read.len() - MAX_READ_ITEM];
+            self.read.retain(|_, v| *v >= threshold);
+        }
+
+        is_changed
+    }
+
+    pub fn marker(&self, language: Language) -> &str {
+        match language {
+            Language::Cn => &self.marker_cn,
+            Language::En => &self.marker_en,
+        }
+    }
+
+    // Return true if the newest seen item is changed
+    pub fn set_last_seen(&mut self, language: Language, items: &[NewsItem]) -> bool {
+        let key = match items.first() {
+            Some(item) => item.key().to_string(),
+            _ => return false,
+        };
+
+        let last_seen = match language {
+            Language::Cn => &mut self.last_seen_cn,
+            Language::En => &mut self.last_seen_en,
+        };
+
+        if *last_seen == key {
+            return false;
+        }
+
+        *last_seen = key;
+        true
+    }
+}
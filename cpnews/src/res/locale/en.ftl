@@ -0,0 +1,116 @@
+locale-name = English
+app-name = Crypto News
+about = About
+refresh = Refresh
+refreshing = Refreshing
+loading = Loading
+source-link = Source Link
+language = Language
+ui-language = Interface Language
+news-language = News Language
+news-language-cn = Chinese News
+news-language-en = English News
+news-language-all = All News (Timeline)
+timeline = Timeline
+chinese-script = Chinese Script
+theme = Theme
+theme-light = Light
+theme-dark = Dark
+theme-system = Follow System
+reading = Reading
+density-comfortable = Comfortable
+density-compact = Compact
+density-headlines-only = Headlines Only
+font-scale = Font Scale
+line-spacing = Line Spacing
+font = Font
+font-proportional = Text
+font-monospace = Numbers
+font-default = Default
+translate = Translate
+translating = Translating
+translate-failed = Translation failed. Reason: { $reason }
+quit = Quit
+move = Move
+clear = Clear
+save = Save
+saved-to = Saved to { $path }
+save-failed = Save failed. Reason: { $reason }
+
+unread-count = { $count ->
+    [one] { $count } unread item
+   *[other] { $count } unread items
+}
+last-visit-divider = You left off here
+news-empty = No news
+
+bookmarks = Bookmarks
+bookmark-add = Bookmark
+bookmark-remove = Remove bookmark
+bookmarks-empty = No bookmarks yet
+bookmark-note-hint = Add a note
+
+export = Export
+export-scope-current = Current list
+export-scope-selected = Selected news ({ $count })
+exported-to = Exported to { $path }
+export-failed = Export failed. Reason: { $reason }
+
+daily-digest = Daily Digest
+
+settings = Settings
+settings-reset = Reset to Defaults
+settings-reset-confirm = Reset all the settings?
+settings-reset-done = The settings are reset
+cancel = Cancel
+settings-general = General
+settings-feed = News Feed
+settings-refresh-interval = Refresh Interval
+settings-refresh-interval-hint = 0 to refresh on demand only
+settings-minutes = {" "}min
+settings-retention-days = Archive Retention
+settings-days = {" "}days
+settings-muted-keywords = Muted Keywords
+settings-one-per-line = One per line
+settings-appearance = Appearance
+settings-fallback-fonts = Fallback Fonts
+settings-enabled = Enabled
+settings-digest-hour = Generated After (Hour)
+settings-digest-top = Items per Topic
+settings-translation = Translation
+settings-translation-url = Server URL
+settings-api-key = API Key
+settings-translation-target = Target Language
+settings-network = Network
+settings-timeout = Timeout
+settings-seconds = {" "}s
+settings-proxy = Proxy
+settings-webhooks = Webhooks
+settings-webhook-url = Webhook URL
+settings-webhook-query = Query
+settings-webhook-secret = Secret
+settings-add-webhook = Add Webhook
+settings-hooks = Command Hooks
+settings-hook-command = Command
+settings-hook-mode = Mode
+hook-mode-item = Each item
+hook-mode-batch = All new items at once
+settings-add-hook = Add Hook
+settings-remove = Remove
+settings-invalid-url = { $field }: invalid URL "{ $url }"
+settings-empty-field = { $field } should not be empty
+
+config-migrated = The settings file is upgraded from version { $from }, the old file is kept as { $path }
+config-newer = The settings file is written by a newer version ({ $version }), the unknown settings are ignored and the file is not changed
+config-read-only = The settings file is written by a newer version or can not be read, the change here is not saved
+config-invalid-fields = These settings are invalid and reset to the defaults: { $fields }
+config-unreadable = The settings file can not be read ({ $error }), the defaults are used and the file is not changed
+config-reloaded = The settings file is changed and reloaded
+config-reload-failed = The changed settings file is unreadable, the current settings are kept
+config-conflict = The settings file is changed by another program, the change here is not saved and the file is reloaded
+file-restored = { $path } is corrupted and restored from the previous version, the corrupted file is kept as { $kept }
+file-corrupted = { $path } is corrupted and no previous version is found, the corrupted file is kept as { $kept }
+
+about-license = Based on egui. Copyright 2022-2030 The Heng30 Company Ltd. All rights reserved. The program is provided AS IS with NO WARRANTY OF ANY KIND, INCLUDING THE WARRANTY OF DESIGN, MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE.
+about-coffee = 🎉❤Buy Me a Coffee(MetaMask)❤🎉
+
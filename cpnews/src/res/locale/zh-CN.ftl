@@ -0,0 +1,113 @@
+locale-name = 简体中文
+app-name = 加密新闻
+about = 关于
+refresh = 刷新
+refreshing = 正在刷新
+loading = 正在加载
+source-link = 原文链接
+language = 语言
+ui-language = 界面语言
+news-language = 新闻语言
+news-language-cn = 中文新闻
+news-language-en = 英文新闻
+news-language-all = 全部新闻(时间线)
+timeline = 时间线
+chinese-script = 简繁转换
+theme = 主题
+theme-light = 浅色
+theme-dark = 深色
+theme-system = 跟随系统
+reading = 阅读
+density-comfortable = 宽松
+density-compact = 紧凑
+density-headlines-only = 仅标题
+font-scale = 字体缩放
+line-spacing = 行距
+font = 字体
+font-proportional = 正文
+font-monospace = 数字
+font-default = 默认
+translate = 翻译
+translating = 正在翻译
+translate-failed = 翻译失败。原因: { $reason }
+quit = 退出
+move = 移动
+clear = 清空
+save = 保存
+saved-to = 已保存到 { $path }
+save-failed = 保存失败。原因：{ $reason }
+
+unread-count = { $count } 条未读
+last-visit-divider = 上次看到这里
+news-empty = 暂无新闻
+
+bookmarks = 收藏夹
+bookmark-add = 收藏
+bookmark-remove = 取消收藏
+bookmarks-empty = 暂无收藏
+bookmark-note-hint = 添加备注
+
+export = 导出
+export-scope-current = 当前列表
+export-scope-selected = 选中的新闻 ({ $count })
+exported-to = 已导出到 { $path }
+export-failed = 导出失败。原因：{ $reason }
+
+daily-digest = 每日摘要
+
+settings = 设置
+settings-reset = 恢复默认
+settings-reset-confirm = 恢复所有默认设置?
+settings-reset-done = 已恢复默认设置
+cancel = 取消
+settings-general = 常规
+settings-feed = 新闻源
+settings-refresh-interval = 刷新间隔
+settings-refresh-interval-hint = 0表示仅手动刷新
+settings-minutes = {" "}分钟
+settings-retention-days = 归档保留
+settings-days = {" "}天
+settings-muted-keywords = 屏蔽关键词
+settings-one-per-line = 每行一个
+settings-appearance = 外观
+settings-fallback-fonts = 后备字体
+settings-enabled = 启用
+settings-digest-hour = 生成时间(时)
+settings-digest-top = 每个话题条数
+settings-translation = 翻译
+settings-translation-url = 服务器地址
+settings-api-key = API密钥
+settings-translation-target = 目标语言
+settings-network = 网络
+settings-timeout = 超时
+settings-seconds = {" "}秒
+settings-proxy = 代理
+settings-webhooks = Webhook
+settings-webhook-url = Webhook地址
+settings-webhook-query = 查询
+settings-webhook-secret = 密钥
+settings-add-webhook = 添加Webhook
+settings-hooks = 命令钩子
+settings-hook-command = 命令
+settings-hook-mode = 模式
+hook-mode-item = 逐条
+hook-mode-batch = 新闻一次性全部
+settings-add-hook = 添加钩子
+settings-remove = 删除
+settings-invalid-url = { $field }: 无效的地址"{ $url }"
+settings-empty-field = { $field }不能为空
+
+config-migrated = 配置文件已从版本{ $from }升级，旧文件保存为{ $path }
+config-newer = 配置文件由更新的版本({ $version })写入，未知的设置已被忽略，且不会修改该文件
+config-read-only = 配置文件由更新的版本写入或无法读取，此处的更改未保存
+config-invalid-fields = 以下设置无效，已恢复默认值：{ $fields }
+config-unreadable = 配置文件无法读取（{ $error }），使用默认设置且不会修改该文件
+config-reloaded = 配置文件已更改并重新加载
+config-reload-failed = 更改后的配置文件无法读取，保留当前设置
+config-conflict = 配置文件已被其他程序更改，此处的更改未保存，将重新加载配置文件
+file-restored = { $path }已损坏，已从上一版本恢复，损坏的文件保存为{ $kept }
+file-corrupted = { $path }已损坏且没有找到上一版本，损坏的文件保存为{ $kept }
+
+about-license = 基于egui。版权2022-2030 Heng30公司有限公司，保留所有权利。该程序按原样提供，不提供任何形式的保证，包括设计，适销性和特定用途的保证。
+about-coffee = 🎉❤给我买一杯咖啡(MetaMask)❤🎉
+
@@ -0,0 +1,276 @@
+use super::{
+    config::Config,
+    export::escape_html,
+    news::{self, Language, NewsItem, Source},
+    version,
+};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use reqwest::Url;
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Request, Response, Server};
+
+const MIN_REFRESH_INTERVAL: u64 = 60;
+const DEFAULT_LIMIT: usize = 50;
+
+// The feed names are the languages, the sources, or `all` for the merged news
+const FEED_NAMES: [&str; 5] = ["all", "cn", "en", "odaily", "cryptocompare"];
+
+type Feeds = Arc<RwLock<HashMap<Source, Vec<NewsItem>>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    fn from_ext(ext: &str) -> Option<Self> {
+        match ext {
+            "rss" => Some(FeedFormat::Rss),
+            "atom" => Some(FeedFormat::Atom),
+            "json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+pub fn run(conf: Config, host: &str, port: u16, interval: u64) -> Result<()> {
+    let (cn_items, en_items) = news::load(&conf.cache_dir);
+    let feeds: Feeds = Arc::new(RwLock::new(HashMap::from([
+        (Source::Odaily, cn_items),
+        (Source::CryptoCompare, en_items),
+    ])));
+
+    {
+        let feeds = feeds.clone();
+        let interval = Duration::from_secs(interval.max(MIN_REFRESH_INTERVAL));
+        thread::spawn(move || loop {
+            for source in Source::ALL {
+                match news::fetch(&conf, source) {
+                    Ok(items) if !items.is_empty() => {
+                        feeds.write().unwrap().insert(source, items);
+                    }
+                    Err(e) => log::warn!("{}: {e}", source.name()),
+                    _ => (),
+                }
+            }
+            thread::sleep(interval);
+        });
+    }
+
+    let server = Server::http((host, port)).map_err(|e| anyhow!("{e}"))?;
+    println!("Serving on http://{host}:{port}");
+
+    for request in server.incoming_requests() {
+        if let Err(e) = handle(request, &feeds) {
+            log::warn!("{e:?}");
+        }
+    }
+
+    Ok(())
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: String) -> Result<()> {
+    let header =
+        Header::from_bytes("Content-Type", content_type).map_err(|_| anyhow!("invalid header"))?;
+
+    request.respond(
+        Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header),
+    )?;
+    Ok(())
+}
+
+fn handle(request: Request, feeds: &Feeds) -> Result<()> {
+    let url = Url::parse(&format!("http://localhost{}", request.url()))?;
+    let path = url.path().trim_start_matches('/');
+
+    if path.is_empty() {
+        return respond(request, 200, "text/html; charset=utf-8", index());
+    }
+
+    let (name, format) = match path.rsplit_once('.') {
+        Some((name, ext)) if FEED_NAMES.contains(&name) => match FeedFormat::from_ext(ext) {
+            Some(format) => (name, format),
+            _ => return respond(request, 404, "text/plain", "Not Found".to_string()),
+        },
+        _ => return respond(request, 404, "text/plain", "Not Found".to_string()),
+    };
+
+    let query = url
+        .query_pairs()
+        .find(|(k, _)| k == "q")
+        .map(|(_, v)| v.to_string())
+        .unwrap_or_default();
+
+    let limit = url
+        .query_pairs()
+        .find(|(k, _)| k == "limit")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_LIMIT);
+
+    let items = {
+        let feeds = feeds.read().unwrap();
+        let mut items = feeds
+            .iter()
+            .filter(|(source, _)| match name {
+                "all" => true,
+                "cn" => source.language() == Language::Cn,
+                "en" => source.language() == Language::En,
+                _ => source.name() == name,
+            })
+            .flat_map(|(_, items)| items.iter())
+            .filter(|item| query.is_empty() || news::matches(item, &query))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        items.sort_by_cached_key(|item| Reverse(item.published_at()));
+        items.truncate(limit);
+        items
+    };
+
+    let title = format!("cpnews - {name}");
+    let body = match format {
+        FeedFormat::Rss => to_rss(&title, &items),
+        FeedFormat::Atom => to_atom(&title, name, &items),
+        FeedFormat::Json => to_json_feed(&title, &items)?,
+    };
+
+    respond(request, 200, format.content_type(), body)
+}
+
+fn index() -> String {
+    let mut links = String::default();
+    for name in FEED_NAMES {
+        links.push_str(&format!("<li>{name}:"));
+        for ext in ["rss", "atom", "json"] {
+            links.push_str(&format!(r#" <a href="/{name}.{ext}">{ext}</a>"#));
+        }
+        links.push_str("</li>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>cpnews</title></head>
+<body>
+<h1>cpnews {}</h1>
+<ul>
+{links}</ul>
+<p>Query parameters: <code>q</code> to filter the news, <code>limit</code> to limit the number of the news.</p>
+</body>
+</html>
+"#,
+        version::VERSION
+    )
+}
+
+fn to_rss(title: &str, items: &[NewsItem]) -> String {
+    let mut entries = String::default();
+    for item in items.iter() {
+        let pub_date = item
+            .published_at()
+            .map(|d| d.to_rfc2822())
+            .unwrap_or_default();
+
+        entries.push_str(&format!(
+            "<item><title>{}</title><link>{}</link><guid>{}</guid><description>{}</description><pubDate>{}</pubDate><category>{}</category></item>\n",
+            escape_html(&item.title),
+            escape_html(&item.link),
+            escape_html(item.key()),
+            escape_html(&item.summary),
+            pub_date,
+            escape_html(&item.source),
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0">
+<channel>
+<title>{}</title>
+<link>/</link>
+<description>{}</description>
+{entries}</channel>
+</rss>
+"#,
+        escape_html(title),
+        escape_html(title),
+    )
+}
+
+fn to_atom(title: &str, name: &str, items: &[NewsItem]) -> String {
+    let rfc3339 = |item: &NewsItem| {
+        item.published_at()
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_default()
+    };
+
+    let mut entries = String::default();
+    for item in items.iter() {
+        entries.push_str(&format!(
+            "<entry><title>{}</title><link href=\"{}\"/><id>{}</id><updated>{}</updated><summary>{}</summary><author><name>{}</name></author></entry>\n",
+            escape_html(&item.title),
+            escape_html(&item.link),
+            escape_html(item.key()),
+            rfc3339(item),
+            escape_html(&item.summary),
+            escape_html(&item.source),
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+<title>{}</title>
+<id>urn:cpnews:{}</id>
+<updated>{}</updated>
+{entries}</feed>
+"#,
+        escape_html(title),
+        escape_html(name),
+        items
+            .first()
+            .map(rfc3339)
+            .unwrap_or_else(|| Utc::now().to_rfc3339()),
+    )
+}
+
+fn to_json_feed(title: &str, items: &[NewsItem]) -> Result<String> {
+    let items = items
+        .iter()
+        .map(|item| {
+            serde_json::json!({
+                "id": item.key(),
+                "url": item.link,
+                "title": item.title,
+                "content_text": item.summary,
+                "date_published": item.published_at().map(|d| d.to_rfc3339()),
+                "authors": [{ "name": item.source }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": title,
+        "items": items,
+    }))?)
+}
@@ -0,0 +1,604 @@
+use super::{
+    app::{App, CurrentPanel, MsgType, MAX_FONT_SCALE, MIN_FONT_SCALE},
+    config::{Config, Density, Hook, HookMode, Webhook},
+    font,
+    news::Language,
+    theme,
+    tr::{self, tr, tr_args},
+    util::Debounce,
+    zh::ChineseScript,
+};
+use egui::{
+    Align, ComboBox, DragValue, FontId, Grid, Layout, Response, RichText, ScrollArea, Slider,
+    TextEdit, Ui,
+};
+use fluent_bundle::FluentArgs;
+use reqwest::{Proxy, Url};
+
+const MAX_REFRESH_INTERVAL_MINS: u64 = 24 * 60;
+const MAX_RETENTION_DAYS: i64 = 3650;
+const MAX_TIMEOUT_SECS: u64 = 600;
+const MAX_DIGEST_TOP: usize = 50;
+
+#[derive(Clone, Debug, Default)]
+pub struct SettingsPanel {
+    // the edited config, which is applied once it is valid
+    draft: Config,
+
+    // the lists are edited as one item per line
+    muted_keywords: String,
+    fallback_fonts: String,
+
+    errors: Vec<String>,
+    is_confirming_reset: bool,
+
+    // the applied config is saved once the edits pause, rather than on every keystroke
+    pub saves: Debounce,
+}
+
+// The edits of a frame, the config is saved once the dragging is over
+#[derive(Default)]
+struct Edits {
+    is_changed: bool,
+    is_dragging: bool,
+    is_released: bool,
+
+    // a text field is left
+    is_committed: bool,
+
+    // the font list is typed, which is loaded once the field is left or the edits pause
+    is_typing_fonts: bool,
+}
+
+impl Edits {
+    fn track(&mut self, response: Response) {
+        self.is_changed |= response.changed();
+        self.is_dragging |= response.dragged();
+        self.is_released |= response.drag_released();
+        self.is_committed |= response.lost_focus();
+    }
+}
+
+fn parse_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+// The texts of the list are only replaced when the list is changed elsewhere, so that the
+// empty line being typed is kept
+fn sync_lines(text: &mut String, items: &[String]) {
+    if parse_lines(text) != items {
+        *text = items.join("\n");
+    }
+}
+
+pub fn open(app: &mut App) {
+    app.settings_panel.errors.clear();
+    app.settings_panel.is_confirming_reset = false;
+    app.current_panel = CurrentPanel::Settings;
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let mut panel = std::mem::take(&mut app.settings_panel);
+
+    // an invalid draft is kept until it is fixed, or else it follows the applied config
+    if panel.errors.is_empty() {
+        panel.draft = app.conf.clone();
+        sync_lines(&mut panel.muted_keywords, &app.conf.feed.muted_keywords);
+        sync_lines(&mut panel.fallback_fonts, &app.conf.font.fallbacks);
+    }
+
+    header(app, &mut panel, ui);
+
+    let mut edits = Edits::default();
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            general_section(ui, &mut panel.draft, &mut edits);
+            feed_section(ui, &mut panel, &mut edits);
+            appearance_section(ui, &mut panel, &mut edits);
+            digest_section(ui, &mut panel.draft, &mut edits);
+            translation_section(ui, &mut panel.draft, &mut edits);
+            network_section(ui, &mut panel.draft, &mut edits);
+            webhooks_section(ui, &mut panel.draft.webhooks, &mut edits);
+            hooks_section(ui, &mut panel.draft.hooks, &mut edits);
+        });
+
+    if edits.is_changed {
+        panel.draft.feed.muted_keywords = parse_lines(&panel.muted_keywords);
+        panel.draft.font.fallbacks = parse_lines(&panel.fallback_fonts);
+        panel.errors = validate(&panel.draft);
+
+        if panel.errors.is_empty() {
+            app.apply_conf(ui.ctx(), panel.draft.clone());
+            if !edits.is_typing_fonts {
+                app.apply_font(ui.ctx());
+            }
+        }
+    }
+
+    if panel.errors.is_empty() && ((edits.is_changed && !edits.is_dragging) || edits.is_released) {
+        panel.saves.touch();
+    }
+
+    if (edits.is_committed && panel.saves.flush()) || panel.saves.is_due(ui.ctx()) {
+        app.save_conf();
+        app.apply_font(ui.ctx());
+    }
+
+    app.settings_panel = panel;
+}
+
+fn header(app: &mut App, panel: &mut SettingsPanel, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        if ui
+            .add(
+                theme::image_and_text_button(
+                    &app.back_icon,
+                    theme::BACK_ICON_SIZE,
+                    RichText::new(tr("settings"))
+                        .font(FontId::proportional(theme::current().news_title_font_size)),
+                )
+                .frame(false),
+            )
+            .clicked()
+        {
+            app.current_panel = CurrentPanel::News;
+        }
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            ui.add_space(theme::current().padding * 2.);
+
+            if !panel.is_confirming_reset {
+                if ui
+                    .add(
+                        theme::image_and_text_button(
+                            &app.reset_icon,
+                            theme::BACK_ICON_SIZE,
+                            tr("settings-reset"),
+                        )
+                        .frame(false),
+                    )
+                    .clicked()
+                {
+                    panel.is_confirming_reset = true;
+                }
+                return;
+            }
+
+            if ui.button(tr("cancel")).clicked() {
+                panel.is_confirming_reset = false;
+            }
+
+            if ui
+                .button(RichText::new(tr("settings-reset")).color(theme::current().danger_color))
+                .clicked()
+            {
+                panel.is_confirming_reset = false;
+                panel.errors.clear();
+                app.apply_conf(ui.ctx(), app.conf.defaults());
+                app.apply_font(ui.ctx());
+                panel.saves.flush();
+                app.save_conf();
+                app.show_message(tr("settings-reset-done"), MsgType::Success);
+            }
+
+            ui.label(
+                RichText::new(tr("settings-reset-confirm")).color(theme::current().light_color),
+            );
+        });
+    });
+
+    for warning in app.conf.warnings.iter() {
+        ui.label(RichText::new(warning.message()).color(theme::current().warn_color));
+    }
+
+    for error in panel.errors.iter() {
+        ui.label(RichText::new(error).color(theme::current().danger_color));
+    }
+
+    ui.add_space(theme::current().spacing);
+}
+
+fn section_title(ui: &mut Ui, id: &str) {
+    ui.add_space(theme::current().spacing * 3.);
+    ui.label(
+        RichText::new(tr(id))
+            .color(theme::current().brand_color)
+            .font(FontId::proportional(theme::current().news_title_font_size)),
+    );
+    ui.separator();
+}
+
+fn settings_grid(ui: &mut Ui, id: &str, add_contents: impl FnOnce(&mut Ui)) {
+    Grid::new(id)
+        .num_columns(2)
+        .spacing([theme::current().spacing * 4., theme::current().spacing * 2.])
+        .show(ui, add_contents);
+}
+
+fn general_section(ui: &mut Ui, conf: &mut Config, edits: &mut Edits) {
+    section_title(ui, "settings-general");
+
+    settings_grid(ui, "settings-general", |ui| {
+        ui.label(tr("ui-language"));
+        ComboBox::from_id_source("settings-locale")
+            .selected_text(tr::locale_name(&conf.ui.locale))
+            .show_ui(ui, |ui| {
+                for locale in tr::locales() {
+                    edits.track(ui.selectable_value(
+                        &mut conf.ui.locale,
+                        locale.to_string(),
+                        tr::locale_name(locale),
+                    ));
+                }
+            });
+        ui.end_row();
+
+        // the timeline is one more choice of the news language
+        ui.label(tr("news-language"));
+        let selected = if conf.ui.timeline {
+            tr("news-language-all")
+        } else {
+            tr(&format!("news-language-{}", conf.ui.language.code()))
+        };
+        ComboBox::from_id_source("settings-news-language")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for language in Language::ALL {
+                    let is_selected = !conf.ui.timeline && conf.ui.language == language;
+                    let text = tr(&format!("news-language-{}", language.code()));
+                    if ui.selectable_label(is_selected, text).clicked() && !is_selected {
+                        conf.ui.language = language;
+                        conf.ui.timeline = false;
+                        edits.is_changed = true;
+                    }
+                }
+
+                if ui
+                    .selectable_label(conf.ui.timeline, tr("news-language-all"))
+                    .clicked()
+                    && !conf.ui.timeline
+                {
+                    conf.ui.timeline = true;
+                    edits.is_changed = true;
+                }
+            });
+        ui.end_row();
+
+        ui.label(tr("chinese-script"));
+        ComboBox::from_id_source("settings-chinese-script")
+            .selected_text(conf.ui.chinese_script.name())
+            .show_ui(ui, |ui| {
+                for script in ChineseScript::ALL {
+                    edits.track(ui.selectable_value(
+                        &mut conf.ui.chinese_script,
+                        script,
+                        script.name(),
+                    ));
+                }
+            });
+        ui.end_row();
+    });
+}
+
+fn feed_section(ui: &mut Ui, panel: &mut SettingsPanel, edits: &mut Edits) {
+    section_title(ui, "settings-feed");
+
+    let conf = &mut panel.draft;
+    settings_grid(ui, "settings-feed", |ui| {
+        ui.label(tr("settings-refresh-interval"));
+        edits.track(
+            ui.add(
+                DragValue::new(&mut conf.feed.refresh_interval_mins)
+                    .clamp_range(0..=MAX_REFRESH_INTERVAL_MINS)
+                    .suffix(tr("settings-minutes")),
+            )
+            .on_hover_text(tr("settings-refresh-interval-hint")),
+        );
+        ui.end_row();
+
+        ui.label(tr("settings-retention-days"));
+        edits.track(
+            ui.add(
+                DragValue::new(&mut conf.archive.retention_days)
+                    .clamp_range(1..=MAX_RETENTION_DAYS)
+                    .suffix(tr("settings-days")),
+            ),
+        );
+        ui.end_row();
+
+        ui.label(tr("settings-muted-keywords"));
+        edits.track(
+            ui.add(
+                TextEdit::multiline(&mut panel.muted_keywords)
+                    .desired_rows(3)
+                    .hint_text(tr("settings-one-per-line")),
+            ),
+        );
+        ui.end_row();
+    });
+}
+
+fn appearance_section(ui: &mut Ui, panel: &mut SettingsPanel, edits: &mut Edits) {
+    section_title(ui, "settings-appearance");
+
+    let conf = &mut panel.draft;
+    let themes = theme::choices(conf);
+    settings_grid(ui, "settings-appearance", |ui| {
+        ui.label(tr("theme"));
+        let selected = themes
+            .iter()
+            .find(|(name, _)| *name == conf.ui.theme)
+            .map_or(conf.ui.theme.clone(), |(_, text)| text.clone());
+        ComboBox::from_id_source("settings-theme")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for (name, text) in themes.into_iter() {
+                    edits.track(ui.selectable_value(&mut conf.ui.theme, name, text));
+                }
+            });
+        ui.end_row();
+
+        ui.label(tr("reading"));
+        ComboBox::from_id_source("settings-density")
+            .selected_text(tr(conf.reading.density.tr_id()))
+            .show_ui(ui, |ui| {
+                for density in Density::ALL {
+                    edits.track(ui.selectable_value(
+                        &mut conf.reading.density,
+                        density,
+                        tr(density.tr_id()),
+                    ));
+                }
+            });
+        ui.end_row();
+
+        ui.label(tr("font-scale"));
+        edits.track(ui.add(Slider::new(
+            &mut conf.reading.font_scale,
+            MIN_FONT_SCALE..=MAX_FONT_SCALE,
+        )));
+        ui.end_row();
+
+        ui.label(tr("line-spacing"));
+        edits.track(ui.add(Slider::new(&mut conf.reading.line_spacing, 1.0..=2.0)));
+        ui.end_row();
+
+        for (id, font) in [
+            ("font-proportional", &mut conf.font.proportional),
+            ("font-monospace", &mut conf.font.monospace),
+        ] {
+            ui.label(tr(id));
+            let selected = if font.is_empty() {
+                tr("font-default")
+            } else {
+                font.clone()
+            };
+            ComboBox::from_id_source(id)
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    edits.track(ui.selectable_value(font, String::default(), tr("font-default")));
+                    for (name, _) in font::system_fonts().iter() {
+                        edits.track(ui.selectable_value(font, name.clone(), name));
+                    }
+                });
+            ui.end_row();
+        }
+
+        ui.label(tr("settings-fallback-fonts"));
+        let response = ui.add(
+            TextEdit::multiline(&mut panel.fallback_fonts)
+                .desired_rows(3)
+                .hint_text(tr("settings-one-per-line")),
+        );
+        edits.is_typing_fonts |= response.changed();
+        edits.track(response);
+        ui.end_row();
+    });
+}
+
+fn digest_section(ui: &mut Ui, conf: &mut Config, edits: &mut Edits) {
+    section_title(ui, "daily-digest");
+
+    settings_grid(ui, "settings-digest", |ui| {
+        ui.label(tr("settings-enabled"));
+        edits.track(ui.checkbox(&mut conf.digest.enabled, ""));
+        ui.end_row();
+
+        ui.label(tr("settings-digest-hour"));
+        edits.track(ui.add(DragValue::new(&mut conf.digest.hour).clamp_range(0..=23)));
+        ui.end_row();
+
+        ui.label(tr("settings-digest-top"));
+        edits.track(ui.add(DragValue::new(&mut conf.digest.top).clamp_range(1..=MAX_DIGEST_TOP)));
+        ui.end_row();
+    });
+}
+
+fn translation_section(ui: &mut Ui, conf: &mut Config, edits: &mut Edits) {
+    section_title(ui, "settings-translation");
+
+    settings_grid(ui, "settings-translation", |ui| {
+        ui.label(tr("settings-enabled"));
+        edits.track(ui.checkbox(&mut conf.translation.enabled, ""));
+        ui.end_row();
+
+        ui.label(tr("settings-translation-url"));
+        edits.track(ui.text_edit_singleline(&mut conf.translation.url));
+        ui.end_row();
+
+        ui.label(tr("settings-api-key"));
+        edits.track(ui.add(TextEdit::singleline(&mut conf.translation.api_key).password(true)));
+        ui.end_row();
+
+        ui.label(tr("settings-translation-target"));
+        edits.track(ui.text_edit_singleline(&mut conf.translation.target));
+        ui.end_row();
+    });
+}
+
+fn network_section(ui: &mut Ui, conf: &mut Config, edits: &mut Edits) {
+    section_title(ui, "settings-network");
+
+    settings_grid(ui, "settings-network", |ui| {
+        ui.label(tr("settings-timeout"));
+        edits.track(
+            ui.add(
+                DragValue::new(&mut conf.network.timeout_secs)
+                    .clamp_range(1..=MAX_TIMEOUT_SECS)
+                    .suffix(tr("settings-seconds")),
+            ),
+        );
+        ui.end_row();
+
+        ui.label(tr("settings-proxy"));
+        edits.track(
+            ui.add(
+                TextEdit::singleline(&mut conf.network.proxy).hint_text("http://127.0.0.1:8080"),
+            ),
+        );
+        ui.end_row();
+    });
+}
+
+fn webhooks_section(ui: &mut Ui, webhooks: &mut Vec<Webhook>, edits: &mut Edits) {
+    section_title(ui, "settings-webhooks");
+
+    let mut removed = None;
+    for (index, webhook) in webhooks.iter_mut().enumerate() {
+        settings_grid(ui, &format!("settings-webhook-{index}"), |ui| {
+            ui.label(tr("settings-webhook-url"));
+            edits.track(ui.text_edit_singleline(&mut webhook.url));
+            ui.end_row();
+
+            ui.label(tr("settings-webhook-query"));
+            edits.track(ui.text_edit_singleline(&mut webhook.query));
+            ui.end_row();
+
+            ui.label(tr("settings-webhook-secret"));
+            edits.track(ui.add(TextEdit::singleline(&mut webhook.secret).password(true)));
+            ui.end_row();
+        });
+
+        if ui.button(tr("settings-remove")).clicked() {
+            removed = Some(index);
+        }
+        ui.separator();
+    }
+
+    if let Some(index) = removed {
+        webhooks.remove(index);
+        edits.is_changed = true;
+    }
+
+    if ui.button(tr("settings-add-webhook")).clicked() {
+        webhooks.push(Webhook::default());
+        edits.is_changed = true;
+    }
+}
+
+fn hooks_section(ui: &mut Ui, hooks: &mut Vec<Hook>, edits: &mut Edits) {
+    section_title(ui, "settings-hooks");
+
+    let mut removed = None;
+    for (index, hook) in hooks.iter_mut().enumerate() {
+        settings_grid(ui, &format!("settings-hook-{index}"), |ui| {
+            ui.label(tr("settings-hook-command"));
+            edits.track(ui.text_edit_singleline(&mut hook.command));
+            ui.end_row();
+
+            ui.label(tr("settings-hook-mode"));
+            ComboBox::from_id_source(format!("settings-hook-mode-{index}"))
+                .selected_text(tr(hook.mode.tr_id()))
+                .show_ui(ui, |ui| {
+                    for mode in HookMode::ALL {
+                        edits.track(ui.selectable_value(&mut hook.mode, mode, tr(mode.tr_id())));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr("settings-timeout"));
+            edits.track(
+                ui.add(
+                    DragValue::new(&mut hook.timeout_secs)
+                        .clamp_range(1..=MAX_TIMEOUT_SECS)
+                        .suffix(tr("settings-seconds")),
+                ),
+            );
+            ui.end_row();
+        });
+
+        if ui.button(tr("settings-remove")).clicked() {
+            removed = Some(index);
+        }
+        ui.separator();
+    }
+
+    if let Some(index) = removed {
+        hooks.remove(index);
+        edits.is_changed = true;
+    }
+
+    if ui.button(tr("settings-add-hook")).clicked() {
+        hooks.push(Hook::default());
+        edits.is_changed = true;
+    }
+
+    ui.add_space(theme::current().spacing * 4.);
+}
+
+fn invalid_url(field: &str, url: &str) -> String {
+    tr_args(
+        "settings-invalid-url",
+        Some(&FluentArgs::from_iter([
+            ("field", tr(field)),
+            ("url", url.to_string()),
+        ])),
+    )
+}
+
+fn empty_field(field: &str) -> String {
+    tr_args(
+        "settings-empty-field",
+        Some(&FluentArgs::from_iter([("field", tr(field))])),
+    )
+}
+
+fn is_http_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| ["http", "https"].contains(&url.scheme()))
+}
+
+// The messages of the invalid settings, the config is only applied without any
+pub fn validate(conf: &Config) -> Vec<String> {
+    let mut errors = vec![];
+
+    // the translation settings may be left empty while the translation is disabled
+    let translation = &conf.translation;
+    if (translation.enabled || !translation.url.is_empty()) && !is_http_url(&translation.url) {
+        errors.push(invalid_url("settings-translation-url", &translation.url));
+    }
+
+    if translation.enabled && translation.target.trim().is_empty() {
+        errors.push(empty_field("settings-translation-target"));
+    }
+
+    let proxy = &conf.network.proxy;
+    if !proxy.is_empty() && (!is_http_url(proxy) || Proxy::all(proxy).is_err()) {
+        errors.push(invalid_url("settings-proxy", proxy));
+    }
+
+    for webhook in conf.webhooks.iter() {
+        if !is_http_url(&webhook.url) {
+            errors.push(invalid_url("settings-webhook-url", &webhook.url));
+        }
+    }
+
+    if conf.hooks.iter().any(|hook| hook.command.trim().is_empty()) {
+        errors.push(empty_field("settings-hook-command"));
+    }
+
+    errors
+}
@@ -0,0 +1,315 @@
+use super::tr::tr_args;
+use anyhow::Result;
+use fluent_bundle::FluentArgs;
+use std::{
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+// Each write has its own temp file, so the threads writing the same file do not mix their texts
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// The corrupted files found while loading, which are reported to the user
+static CORRUPTED: Mutex<Vec<Corrupted>> = Mutex::new(vec![]);
+
+#[derive(Clone, Debug)]
+pub struct Corrupted {
+    pub path: PathBuf,
+
+    // the corrupted file is moved aside rather than overwritten by the next write
+    pub kept_as: PathBuf,
+
+    // the previous generation of the file is used instead
+    pub is_restored: bool,
+}
+
+impl Corrupted {
+    pub fn message(&self) -> String {
+        let id = if self.is_restored {
+            "file-restored"
+        } else {
+            "file-corrupted"
+        };
+
+        tr_args(
+            id,
+            Some(&FluentArgs::from_iter([
+                ("path", self.path.display().to_string()),
+                ("kept", self.kept_as.display().to_string()),
+            ])),
+        )
+    }
+}
+
+// e.g. `news-cn.json.bak` for `news-cn.json`
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}"));
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+// A symlink, e.g. of a dotfiles manager, is kept and the file it points to is used, even if the
+// file is gone, e.g. moved aside as corrupted
+fn resolve(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+
+    match fs::read_link(path) {
+        Ok(target) => path
+            .parent()
+            .map_or(target.clone(), |dir| dir.join(&target)),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Err(e) = File::open(dir).and_then(|dir| dir.sync_all()) {
+            log::debug!("{}: {e:?}", dir.display());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_: &Path) {}
+
+// Write the file through a synced temp file, which is renamed over the file, so a crash leaves
+// either the old or the new file and a reader never finds the file missing. The old file is
+// kept as the previous generation
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let path = resolve(path);
+
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp_path = with_suffix(&path, &format!("{}-{counter}.tmp", process::id()));
+
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_ref())?;
+        file.sync_all()
+    });
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+
+    if path.exists() {
+        backup(&path)?;
+    }
+    fs::rename(&temp_path, &path)?;
+    sync_dir(&path);
+
+    Ok(())
+}
+
+// Keep the file as the previous generation, while the file itself stays in place
+fn backup(path: &Path) -> Result<()> {
+    let bak = backup_path(path);
+    match fs::remove_file(&bak) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => (),
+    }
+
+    if fs::hard_link(path, &bak).is_err() {
+        fs::copy(path, &bak)?;
+    }
+
+    Ok(())
+}
+
+fn report(corrupted: Corrupted) {
+    log::warn!("{corrupted:?}");
+    CORRUPTED.lock().unwrap().push(corrupted);
+}
+
+// The corrupted files found since the last call
+pub fn take_corrupted() -> Vec<Corrupted> {
+    std::mem::take(&mut *CORRUPTED.lock().unwrap())
+}
+
+// Take the corrupted file found at the path, which is reported by the caller instead
+pub fn take_corrupted_of(path: &Path) -> Option<Corrupted> {
+    let mut corrupted = CORRUPTED.lock().unwrap();
+    let index = corrupted.iter().position(|c| c.path == path)?;
+    Some(corrupted.remove(index))
+}
+
+fn read_text(path: &Path) -> std::io::Result<Option<String>> {
+    fs::read(path).map(|bytes| String::from_utf8(bytes).ok())
+}
+
+// Read the file, or its previous generation if the file is corrupted, e.g. cut in the middle of
+// a character by a crash. `None` if neither of them is found or valid
+pub fn read_with<T>(path: &Path, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    // the backup is next to the file the symlink points to, where `write` puts it
+    let link = path;
+    let path = &resolve(path);
+
+    let kept_as = match read_text(path) {
+        Ok(text) => match text.as_deref().and_then(&parse) {
+            Some(value) => return Some(value),
+            None => {
+                let kept_as = with_suffix(path, "corrupt");
+                if let Err(e) = fs::rename(path, &kept_as) {
+                    log::warn!("{}: {e:?}", path.display());
+                }
+                Some(kept_as)
+            }
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            log::warn!("{}: {e:?}", path.display());
+            return None;
+        }
+    };
+
+    let bak = backup_path(path);
+    let restored = read_text(&bak)
+        .ok()
+        .flatten()
+        .and_then(|text| parse(&text).map(|value| (text, value)));
+
+    match kept_as {
+        Some(kept_as) => {
+            // put the previous generation back in place of the corrupted file. A missing file
+            // is left to the next write, as it may be written right now by another process
+            if let Some((text, _)) = &restored {
+                if let Err(e) = write(path, text) {
+                    log::warn!("{}: {e:?}", path.display());
+                }
+            }
+
+            report(Corrupted {
+                path: link.to_path_buf(),
+                kept_as,
+                is_restored: restored.is_some(),
+            });
+        }
+        None if restored.is_some() => {
+            log::warn!("{} is read from the backup", path.display())
+        }
+        None => (),
+    }
+
+    restored.map(|(_, value)| value)
+}
+
+// Read the JSON file with the fallback to its previous generation
+pub fn load<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    read_with(path, |text| serde_json::from_str::<T>(text).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An empty directory of the test, removed by the next run
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cpnews-store-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn parse(text: &str) -> Option<Vec<u32>> {
+        serde_json::from_str(text).ok()
+    }
+
+    #[test]
+    fn write_keeps_previous_generation() {
+        let path = test_dir("write").join("items.json");
+
+        write(&path, "[1]").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write(&path, "[2]").unwrap();
+        write(&path, "[3]").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[3]");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "[2]");
+    }
+
+    #[test]
+    fn truncated_file_is_restored_from_backup() {
+        let path = test_dir("restore").join("items.json");
+        write(&path, "[1]").unwrap();
+        write(&path, "[1, 2]").unwrap();
+        fs::write(&path, "[1, 2").unwrap();
+
+        assert_eq!(read_with(&path, parse), Some(vec![1]));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[1]");
+        assert_eq!(
+            fs::read_to_string(with_suffix(&path, "corrupt")).unwrap(),
+            "[1, 2"
+        );
+
+        let corrupted = take_corrupted_of(&path).unwrap();
+        assert!(corrupted.is_restored);
+    }
+
+    #[test]
+    fn truncated_file_without_backup() {
+        let path = test_dir("no-backup").join("items.json");
+        fs::write(&path, "[1, 2").unwrap();
+
+        assert_eq!(read_with(&path, parse), None);
+        assert!(!path.exists());
+        assert!(with_suffix(&path, "corrupt").exists());
+
+        let corrupted = take_corrupted_of(&path).unwrap();
+        assert!(!corrupted.is_restored);
+    }
+
+    #[test]
+    fn invalid_utf8_is_corrupted() {
+        let path = test_dir("utf8").join("items.json");
+        write(&path, "[1]").unwrap();
+        write(&path, "[2]").unwrap();
+        fs::write(&path, b"[\"\xe6\xaf").unwrap();
+
+        assert_eq!(read_with(&path, parse), Some(vec![1]));
+        assert!(take_corrupted_of(&path).unwrap().is_restored);
+    }
+
+    #[test]
+    fn missing_file_is_read_from_backup_and_left_missing() {
+        let path = test_dir("missing").join("items.json");
+        write(&path, "[1]").unwrap();
+        write(&path, "[2]").unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_with(&path, parse), Some(vec![1]));
+        assert!(!path.exists());
+        assert!(take_corrupted_of(&path).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_is_kept() {
+        let dir = test_dir("symlink");
+        let target = dir.join("target.json");
+        let link = dir.join("link.json");
+        write(&target, "[1]").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write(&link, "[2]").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "[2]");
+        assert_eq!(fs::read_to_string(backup_path(&target)).unwrap(), "[1]");
+
+        fs::write(&target, "[2").unwrap();
+        assert_eq!(read_with(&link, parse), Some(vec![1]));
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(take_corrupted_of(&link).unwrap().path, link);
+    }
+}
@@ -1,63 +1,254 @@
+use super::{config::Config, font, tr::tr};
+use anyhow::{anyhow, Result};
 use egui::{
-    Color32, ColorImage, Context, FontData, FontDefinitions, FontFamily, Style, Vec2, Visuals,
+    Button, Color32, ColorImage, Context, ImageButton, Response, Style, TextureHandle, Ui, Vec2,
+    Visuals, WidgetText,
 };
+use std::{fs, path::PathBuf, sync::RwLock};
 
-#[allow(unused)]
-pub const PADDING: f32 = 4.;
-
-pub const SPACING: f32 = 4.;
 pub const ICON_SIZE: Vec2 = Vec2::new(24.0, 24.0);
 pub const SMALL_ICON_SIZE: Vec2 = Vec2::new(20.0, 20.0);
 pub const BACK_ICON_SIZE: Vec2 = Vec2::new(16.0, 16.0);
 
-pub const NEWS_TITLE_FONT_SIZE: f32 = 16.0;
-pub const NEWS_SUMMARY_FONT_SIZE: f32 = 15.0;
-pub const NEWS_TITLE_COLOR: Color32 = Color32::from_rgb(0, 0, 200);
+pub const LIGHT_THEME: &str = "light";
+pub const DARK_THEME: &str = "dark";
+pub const SYSTEM_THEME: &str = "system";
+
+static THEME: RwLock<Theme> = RwLock::new(Theme::light());
+
+// The colors are written as `#rrggbb` or `#rrggbbaa` in the theme files
+mod hex_color {
+    use egui::Color32;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color32, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        if a == u8::MAX {
+            serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}"))
+        } else {
+            serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let hex = text.trim_start_matches('#');
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|v| u8::from_str_radix(v, 16).ok())
+                .ok_or(Error::custom(format!("invalid color `{text}`")))
+        };
+
+        match hex.len() {
+            6 => Ok(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?)),
+            8 => Ok(Color32::from_rgba_unmultiplied(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => Err(Error::custom(format!("invalid color `{text}`"))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Theme {
+    // base visuals of egui
+    pub dark: bool,
+
+    #[serde(with = "hex_color")]
+    pub brand_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub news_title_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub read_news_title_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub light_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub badge_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub info_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub success_color: Color32,
 
-pub const BRAND_COLOR: Color32 = Color32::from_rgb(0, 0, 139);
-pub const LIGHT_COLOR: Color32 = Color32::GRAY;
-pub const INFO_COLOR: Color32 = Color32::from_rgb(144, 144, 153);
-pub const SUCCESS_COLOR: Color32 = Color32::from_rgb(102, 194, 58);
-pub const WARN_COLOR: Color32 = Color32::from_rgb(230, 162, 60);
-pub const DANGER_COLOR: Color32 = Color32::from_rgb(245, 108, 108);
+    #[serde(with = "hex_color")]
+    pub warn_color: Color32,
+
+    #[serde(with = "hex_color")]
+    pub danger_color: Color32,
+
+    pub news_title_font_size: f32,
+    pub news_summary_font_size: f32,
+    pub spacing: f32,
+    pub padding: f32,
+}
+
+impl Theme {
+    pub const fn light() -> Self {
+        Self {
+            dark: false,
+            brand_color: Color32::from_rgb(0, 0, 139),
+            news_title_color: Color32::from_rgb(0, 0, 200),
+            read_news_title_color: Color32::from_rgb(100, 100, 170),
+            light_color: Color32::GRAY,
+            badge_color: Color32::from_rgb(100, 149, 237),
+            info_color: Color32::from_rgb(144, 144, 153),
+            success_color: Color32::from_rgb(102, 194, 58),
+            warn_color: Color32::from_rgb(230, 162, 60),
+            danger_color: Color32::from_rgb(245, 108, 108),
+            news_title_font_size: 16.0,
+            news_summary_font_size: 15.0,
+            spacing: 4.0,
+            padding: 4.0,
+        }
+    }
+
+    pub const fn dark() -> Self {
+        Self {
+            dark: true,
+            brand_color: Color32::from_rgb(138, 180, 248),
+            news_title_color: Color32::from_rgb(138, 180, 248),
+            read_news_title_color: Color32::from_rgb(130, 140, 170),
+            light_color: Color32::from_rgb(150, 150, 150),
+            badge_color: Color32::from_rgb(60, 100, 170),
+            ..Self::light()
+        }
+    }
+}
+
+pub fn current() -> Theme {
+    *THEME.read().unwrap()
+}
+
+fn themes_dir(conf: &Config) -> PathBuf {
+    conf.config_path.with_file_name("themes")
+}
+
+// The names of the user theme files in the themes directory, e.g. `solarized` for `solarized.toml`
+pub fn user_themes(conf: &Config) -> Vec<String> {
+    let mut names = fs::read_dir(themes_dir(conf))
+        .map(|dir| {
+            dir.filter_map(|entry| {
+                let path = entry.ok()?.path();
+                match path.extension()?.to_str()? {
+                    "toml" | "json" => Some(path.file_stem()?.to_string_lossy().to_string()),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(not(target_os = "android"))]
+fn is_system_dark() -> bool {
+    dark_light::detect() == dark_light::Mode::Dark
+}
+
+#[cfg(target_os = "android")]
+fn is_system_dark() -> bool {
+    SYSTEM_DARK.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+// Set by the activity, the system setting can not be read without it on Android
+#[cfg(target_os = "android")]
+pub static SYSTEM_DARK: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+// A user theme only overrides the fields it has, over the light or the dark theme set by `base`
+fn load_user_theme(conf: &Config, name: &str) -> Result<Theme> {
+    let dir = themes_dir(conf);
+    let mut value = if let Ok(text) = fs::read_to_string(dir.join(format!("{name}.toml"))) {
+        toml::from_str::<serde_json::Value>(&text)?
+    } else {
+        let text = fs::read_to_string(dir.join(format!("{name}.json")))?;
+        serde_json::from_str::<serde_json::Value>(&text)?
+    };
+
+    let overrides = value
+        .as_object_mut()
+        .ok_or(anyhow!("{name}: a theme should be a table"))?;
+
+    let base = match overrides.remove("base").as_ref().and_then(|v| v.as_str()) {
+        Some(DARK_THEME) => Theme::dark(),
+        _ => Theme::light(),
+    };
+
+    let mut theme = serde_json::to_value(base)?;
+    for (k, v) in overrides.iter() {
+        theme[k] = v.clone();
+    }
+
+    Ok(serde_json::from_value::<Theme>(theme)?)
+}
+
+// The names of the preset and the user themes with the texts to show
+pub fn choices(conf: &Config) -> Vec<(String, String)> {
+    let presets = [LIGHT_THEME, DARK_THEME, SYSTEM_THEME]
+        .into_iter()
+        .map(|name| (name.to_string(), tr(&format!("theme-{name}"))));
+
+    let user_themes = user_themes(conf)
+        .into_iter()
+        .map(|name| (name.clone(), name));
+
+    presets.chain(user_themes).collect()
+}
+
+pub fn load(conf: &Config) -> Theme {
+    match conf.ui.theme.as_str() {
+        LIGHT_THEME => Theme::light(),
+        DARK_THEME => Theme::dark(),
+        SYSTEM_THEME if is_system_dark() => Theme::dark(),
+        SYSTEM_THEME => Theme::light(),
+        name => load_user_theme(conf, name).unwrap_or_else(|e| {
+            log::warn!("{e:?}");
+            Theme::light()
+        }),
+    }
+}
+
+pub fn set_theme(ctx: &Context, theme: Theme) {
+    *THEME.write().unwrap() = theme;
+    set_style(ctx);
+}
 
 pub const REFRESH_ICON: &[u8] = include_bytes!("./res/image/refresh.png");
 pub const LANGUAGE_ICON: &[u8] = include_bytes!("./res/image/lang.png");
 pub const BRAND_ICON: &[u8] = include_bytes!("./res/image/brand.png");
 pub const BACK_ICON: &[u8] = include_bytes!("./res/image/back-simple.png");
 pub const ABOUT_ICON: &[u8] = include_bytes!("./res/image/about.png");
+pub const RESET_ICON: &[u8] = include_bytes!("./res/image/reset.png");
 
 pub fn init(ctx: &Context) {
-    set_font(ctx);
+    font::init(ctx);
     set_style(ctx);
 }
 
 fn set_style(ctx: &Context) {
-    ctx.set_visuals(Visuals::light());
+    let theme = current();
+    ctx.set_visuals(if theme.dark {
+        Visuals::dark()
+    } else {
+        Visuals::light()
+    });
 
     let mut style: Style = (*ctx.style()).clone();
     style.spacing.scroll_bar_width = 2.0;
-    style.visuals.hyperlink_color = LIGHT_COLOR;
+    style.visuals.hyperlink_color = theme.light_color;
     ctx.set_style(style);
 }
 
-fn set_font(ctx: &Context) {
-    let mut fonts = FontDefinitions::default();
-
-    fonts.font_data.insert(
-        "SourceHanSerifCN".to_owned(),
-        FontData::from_static(include_bytes!("./res/font/SourceHanSerifCN.ttf")),
-    );
-
-    fonts
-        .families
-        .get_mut(&FontFamily::Proportional)
-        .unwrap()
-        .insert(0, "SourceHanSerifCN".to_owned());
-
-    ctx.set_fonts(fonts);
-}
-
 pub fn load_image_from_memory(image_data: &[u8]) -> ColorImage {
     let image = image::load_from_memory(image_data).unwrap();
     let size = [image.width() as _, image.height() as _];
@@ -65,3 +256,27 @@ pub fn load_image_from_memory(image_data: &[u8]) -> ColorImage {
     let pixels = image_buffer.as_flat_samples();
     ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
 }
+
+// The icons are loaded in the background, the fallback text is shown until then
+pub fn image_button(
+    ui: &mut Ui,
+    icon: &Option<TextureHandle>,
+    size: Vec2,
+    fallback: &str,
+) -> Response {
+    match icon {
+        Some(icon) => ui.add(ImageButton::new(icon.id(), size).frame(false)),
+        None => ui.add(Button::new(fallback).frame(false)),
+    }
+}
+
+pub fn image_and_text_button(
+    icon: &Option<TextureHandle>,
+    size: Vec2,
+    text: impl Into<WidgetText>,
+) -> Button {
+    match icon {
+        Some(icon) => Button::image_and_text(icon.id(), size, text),
+        None => Button::new(text),
+    }
+}
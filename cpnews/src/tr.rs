@@ -1,56 +1,124 @@
-use std::collections::HashMap;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
 
-pub fn tr(is_cn: bool, text: &str) -> String {
-    if is_cn {
+pub const DEFAULT_LOCALE: &str = "en";
+
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("./res/locale/en.ftl")),
+    ("zh-CN", include_str!("./res/locale/zh-CN.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+static BUNDLES: OnceLock<Vec<(&'static str, Bundle)>> = OnceLock::new();
+static LOCALE: RwLock<String> = RwLock::new(String::new());
+
+// The catalogs are parsed on the first lookup and kept for the lifetime of the app
+fn bundles() -> &'static Vec<(&'static str, Bundle)> {
+    BUNDLES.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .filter_map(|(locale, source)| {
+                let langid = locale.parse::<LanguageIdentifier>().ok()?;
+                let resource = match FluentResource::try_new(source.to_string()) {
+                    Ok(resource) => resource,
+                    Err((resource, errors)) => {
+                        log::warn!("{locale}: {errors:?}");
+                        resource
+                    }
+                };
+
+                let mut bundle = Bundle::new_concurrent(vec![langid]);
+
+                // egui can not render the unicode isolation marks around the arguments
+                bundle.set_use_isolating(false);
+
+                if let Err(errors) = bundle.add_resource(resource) {
+                    log::warn!("{locale}: {errors:?}");
+                }
+
+                Some((*locale, bundle))
+            })
+            .collect()
+    })
+}
+
+pub fn locales() -> Vec<&'static str> {
+    CATALOGS.iter().map(|(locale, _)| *locale).collect()
+}
+
+// The name of the locale in its own language, e.g. `中文` for `zh-CN`
+pub fn locale_name(locale: &str) -> String {
+    bundles()
+        .iter()
+        .find(|(l, _)| *l == locale)
+        .and_then(|(_, bundle)| {
+            let pattern = bundle.get_message("locale-name")?.value()?;
+            Some(
+                bundle
+                    .format_pattern(pattern, None, &mut vec![])
+                    .to_string(),
+            )
+        })
+        .unwrap_or(locale.to_string())
+}
+
+pub fn set_locale(locale: &str) {
+    *LOCALE.write().unwrap() = locale.to_string();
+}
+
+pub fn locale() -> String {
+    let locale = LOCALE.read().unwrap();
+    if locale.is_empty() {
+        DEFAULT_LOCALE.to_string()
+    } else {
+        locale.clone()
+    }
+}
+
+// The requested locale, then the locales of the same language, then the default locale
+fn fallback_chain(locale: &str) -> Vec<&'static Bundle> {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    let bundles = bundles();
+
+    let exact = bundles
+        .iter()
+        .filter(|(l, _)| l.eq_ignore_ascii_case(locale));
+    let same_language = bundles.iter().filter(|(l, _)| {
+        !l.eq_ignore_ascii_case(locale)
+            && l.split('-')
+                .next()
+                .is_some_and(|v| v.eq_ignore_ascii_case(language))
+    });
+    let default = bundles.iter().filter(|(l, _)| *l == DEFAULT_LOCALE);
+
+    exact
+        .chain(same_language)
+        .chain(default)
+        .map(|(_, bundle)| bundle)
+        .collect()
+}
+
+pub fn tr(id: &str) -> String {
+    tr_args(id, None)
+}
+
+pub fn tr_args(id: &str, args: Option<&FluentArgs>) -> String {
+    for bundle in fallback_chain(&locale()) {
+        let pattern = match bundle.get_message(id).and_then(|msg| msg.value()) {
+            Some(pattern) => pattern,
+            _ => continue,
+        };
+
+        let mut errors = vec![];
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            log::warn!("{id}: {errors:?}");
+        }
         return text.to_string();
     }
 
-    let mut items: HashMap<&str, &str> = HashMap::new();
-    items.insert("出错", "Error");
-    items.insert("原因", "Reason");
-    items.insert("删除成功", "Delete success");
-    items.insert("删除失败", "Delete failed");
-    items.insert("添加成功", "Add success");
-    items.insert("添加失败", "Add failed");
-    items.insert("复制失败", "Copy failed");
-    items.insert("复制成功", "Copy success");
-    items.insert("清空失败", "Delete failed");
-    items.insert("清空成功", "Delete success");
-    items.insert("保存失败", "Save failed");
-    items.insert("保存成功", "Save success");
-    items.insert("重置成功", "Reset success");
-    items.insert("刷新成功", "Flush success");
-    items.insert("发送失败", "Send failed");
-    items.insert("下载成功", "Download success");
-    items.insert("下载失败", "Download failed");
-    items.insert("加载失败", "Load failed");
-    items.insert("密码错误", "Password Invalid");
-    items.insert("正在重试...", "Retrying...");
-    items.insert("正在下载...", "Downloading...");
-    items.insert("创建账户成功", "Create account success");
-    items.insert("创建账户失败", "Create account failed");
-    items.insert("密码错误", "Wrong password");
-    items.insert("修改密码成功", "Change password success");
-    items.insert("切换网络成功", "Switch network success");
-    items.insert("非法输入", "Invalid input");
-    items.insert("生成交易失败", "Generate transaction failed");
-    items.insert("发送交易成功", "Send transaction success");
-    items.insert("发送交易失败", "Send transaction failed");
-    items.insert("非法交易", "Invalid transaction");
-    items.insert("写入成功", "Write file success");
-    items.insert("取消成功", "Cancel success");
-    items.insert("解码成功", "Decode success");
-    items.insert("文件名为空", "File name is empty");
-    items.insert("非法文件", "Invalid file");
-    items.insert("加密新闻", "Crypto News");
-    items.insert("原文链接", "Source Link");
-    items.insert("刷新", "Refresh");
-    items.insert("正在刷新", "Refreshing");
-    items.insert("关于", "About");
-    items.insert("在线", "Online");
-    items.insert("正忙", "Busy");
-    items.insert("空闲", "Idle");
-    items.insert("中文", "En");
-
-    items.get(text).unwrap_or(&text).to_string()
+    // show the id rather than nothing for a missing message
+    id.to_string()
 }
@@ -0,0 +1,144 @@
+use super::{
+    config::{Network, Translation},
+    news::{Language, NewsItem},
+    store, util,
+};
+use anyhow::{anyhow, Result};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[cfg(target_os = "android")]
+const MAX_TRANSLATION_ITEM: usize = 300;
+
+#[cfg(not(target_os = "android"))]
+const MAX_TRANSLATION_ITEM: usize = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Translated {
+    pub title: String,
+    pub summary: String,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Debug)]
+struct TranslateRequest<'a> {
+    q: Vec<&'a str>,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+
+    #[serde(skip_serializing_if = "str::is_empty")]
+    api_key: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct TranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: Vec<String>,
+}
+
+// Translations are cached by the target language and the item key, so each item is translated once
+#[derive(Clone, Debug, Default)]
+pub struct Translations {
+    path: PathBuf,
+    items: HashMap<String, Translated>,
+}
+
+impl Translations {
+    pub fn load(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            items: store::load::<HashMap<String, Translated>>(path).unwrap_or_default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        match serde_json::to_string_pretty(&self.items) {
+            Ok(text) => store::write(&self.path, text),
+            Err(e) => Err(anyhow!("{e:?}")),
+        }
+    }
+
+    fn cache_key(target: &str, item: &NewsItem) -> String {
+        format!("{target}:{}", item.key())
+    }
+
+    pub fn get(&self, target: &str, item: &NewsItem) -> Option<&Translated> {
+        self.items.get(&Self::cache_key(target, item))
+    }
+
+    pub fn insert(&mut self, target: &str, item: &NewsItem, translated: Translated) {
+        self.items.insert(Self::cache_key(target, item), translated);
+
+        // drop the oldest translations
+        if self.items.len() > MAX_TRANSLATION_ITEM {
+            let mut timestamps = self
+                .items
+                .values()
+                .map(|t| t.created_at)
+                .collect::<Vec<_>>();
+            timestamps.sort_unstable();
+            let threshold = timestamps[self.items.len() - MAX_TRANSLATION_ITEM];
+            self.items.retain(|_, t| t.created_at >= threshold);
+        }
+
+        if let Err(e) = self.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+// Language code of the translation API
+fn language_code(language: Language) -> &'static str {
+    match language {
+        Language::Cn => "zh",
+        Language::En => "en",
+    }
+}
+
+// Only the news in another language than the target is translated, the news of an unknown
+// language is left to the server to detect
+pub fn is_foreign(conf: &Translation, language: Option<Language>) -> bool {
+    let target = conf.target.split(['-', '_']).next().unwrap_or_default();
+    language.is_none_or(|l| !language_code(l).eq_ignore_ascii_case(target))
+}
+
+// Translate the item through a LibreTranslate compatible `/translate` API
+pub fn translate(
+    conf: &Translation,
+    network: &Network,
+    language: Option<Language>,
+    item: &NewsItem,
+) -> Result<Translated> {
+    let client = util::http_client(network)?.timeout(TIMEOUT).build()?;
+
+    let req = TranslateRequest {
+        q: vec![&item.title, &item.summary],
+        source: language.map_or("auto", language_code),
+        target: &conf.target,
+        format: "text",
+        api_key: &conf.api_key,
+    };
+
+    let url = format!("{}/translate", conf.url.trim_end_matches('/'));
+    let resp = client.post(&url).json(&req).send()?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("{url} responded {}", resp.status()));
+    }
+
+    let mut texts = resp
+        .json::<TranslateResponse>()?
+        .translated_text
+        .into_iter();
+
+    Ok(Translated {
+        title: texts.next().unwrap_or_default(),
+        summary: texts.next().unwrap_or_default(),
+        created_at: util::timestamp(),
+    })
+}
@@ -0,0 +1,329 @@
+use super::{
+    config::Config,
+    news::{self, Language, NewsItem},
+    tr::{self, tr},
+    zh,
+};
+use anyhow::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const PAGE_SIZE: usize = 10;
+
+enum ChannelItem {
+    ErrMsg(String),
+    NewsItems((Language, Vec<NewsItem>)),
+    FetchDone,
+}
+
+struct Tui {
+    conf: Config,
+    is_fetching: bool,
+    news_items_cn: Vec<NewsItem>,
+    news_items_en: Vec<NewsItem>,
+    list_state: ListState,
+    msg: String,
+
+    tx: Sender<ChannelItem>,
+    rx: Receiver<ChannelItem>,
+}
+
+pub fn run(conf: Config) -> Result<()> {
+    let (news_items_cn, news_items_en) = news::load(conf.cache_dir.as_path());
+    let (tx, rx) = mpsc::channel();
+    tr::set_locale(&conf.ui.locale);
+
+    let mut tui = Tui {
+        conf,
+        is_fetching: false,
+        news_items_cn,
+        news_items_en,
+        list_state: ListState::default().with_selected(Some(0)),
+        msg: String::default(),
+        tx,
+        rx,
+    };
+
+    // the log on stderr would garble the screen. The corrupted files found meanwhile are still
+    // reported once the TUI exits
+    let log_level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+
+    let mut terminal = ratatui::init();
+    let result = tui.run(&mut terminal);
+    ratatui::restore();
+
+    log::set_max_level(log_level);
+    result
+}
+
+impl Tui {
+    fn languages(&self) -> Vec<Language> {
+        if self.conf.ui.timeline {
+            Language::ALL.to_vec()
+        } else {
+            vec![self.conf.ui.language]
+        }
+    }
+
+    fn news_items(&self) -> Vec<(Language, &NewsItem)> {
+        let mut items = news::timeline(self.languages().into_iter().map(|language| {
+            let items = match language {
+                Language::Cn => &self.news_items_cn,
+                Language::En => &self.news_items_en,
+            };
+            (language, &items[..])
+        }));
+
+        items.retain(|(_, item)| !news::is_muted(item, &self.conf.feed.muted_keywords));
+        items
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.fetch_data();
+
+        loop {
+            self.update_data();
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(POLL_INTERVAL)? {
+                continue;
+            }
+
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('j') | KeyCode::Down => self.select(1),
+                KeyCode::Char('k') | KeyCode::Up => self.select(-1),
+                KeyCode::PageDown => self.select(PAGE_SIZE as isize),
+                KeyCode::PageUp => self.select(-(PAGE_SIZE as isize)),
+                KeyCode::Char('g') | KeyCode::Home => self.list_state.select(Some(0)),
+                KeyCode::Char('G') | KeyCode::End => {
+                    let len = self.news_items().len();
+                    self.list_state.select(Some(len.saturating_sub(1)));
+                }
+                KeyCode::Char('r') => self.fetch_data(),
+                KeyCode::Char('l') => self.toggle_language(),
+                KeyCode::Char('t') => self.toggle_timeline(),
+                KeyCode::Enter | KeyCode::Char('o') => self.open_link(),
+                _ => (),
+            }
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.news_items().len();
+        if len == 0 {
+            return;
+        }
+
+        let index = self.list_state.selected().unwrap_or(0) as isize + offset;
+        self.list_state
+            .select(Some(index.clamp(0, len as isize - 1) as usize));
+    }
+
+    fn toggle_language(&mut self) {
+        self.conf.ui.language = match self.conf.ui.language {
+            Language::Cn => Language::En,
+            Language::En => Language::Cn,
+        };
+        self.conf.ui.timeline = false;
+        self.on_feed_changed();
+    }
+
+    fn toggle_timeline(&mut self) {
+        self.conf.ui.timeline = !self.conf.ui.timeline;
+        self.on_feed_changed();
+    }
+
+    fn on_feed_changed(&mut self) {
+        if let Err(e) = self.conf.save() {
+            self.msg = e.to_string();
+        }
+
+        self.list_state.select(Some(0));
+
+        // fetch data only without news cache
+        if self.languages().into_iter().any(|language| match language {
+            Language::Cn => self.news_items_cn.is_empty(),
+            Language::En => self.news_items_en.is_empty(),
+        }) {
+            self.fetch_data();
+        }
+    }
+
+    fn open_link(&mut self) {
+        let link = match self.list_state.selected() {
+            Some(index) => match self.news_items().get(index) {
+                Some((_, item)) => item.link.clone(),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        if link.is_empty() {
+            return;
+        }
+
+        // there is no browser on a remote box, show the link so that it can be copied
+        if webbrowser::open(&link).is_err() {
+            self.msg = link;
+        }
+    }
+
+    fn fetch_data(&mut self) {
+        if self.is_fetching {
+            return;
+        }
+
+        self.is_fetching = true;
+        let tx = self.tx.clone();
+        let languages = self.languages();
+        let conf = self.conf.clone();
+
+        std::thread::spawn(move || {
+            for language in languages {
+                match news::fetch(&conf, language.source()) {
+                    Err(e) => {
+                        let _ = tx.send(ChannelItem::ErrMsg(e.to_string()));
+                    }
+                    Ok(v) => {
+                        let _ = tx.send(ChannelItem::NewsItems((language, v)));
+                    }
+                }
+            }
+            let _ = tx.send(ChannelItem::FetchDone);
+        });
+    }
+
+    fn update_data(&mut self) {
+        while let Ok(item) = self.rx.try_recv() {
+            match item {
+                ChannelItem::ErrMsg(msg) => self.msg = msg,
+                ChannelItem::FetchDone => self.is_fetching = false,
+                ChannelItem::NewsItems((language, items)) => {
+                    if !items.is_empty() {
+                        match language {
+                            Language::Cn => self.news_items_cn = items,
+                            Language::En => self.news_items_en = items,
+                        }
+                        self.msg.clear();
+                    }
+                }
+            }
+
+            self.select(0);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header_area, list_area, detail_area, footer_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let mut header = vec![Span::styled(
+            tr("app-name"),
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )];
+        if self.is_fetching {
+            header.push(Span::raw("  "));
+            header.push(Span::styled(
+                tr("refreshing"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        frame.render_widget(Line::from(header), header_area);
+
+        let is_timeline = self.conf.ui.timeline;
+        let script = self.conf.ui.chinese_script;
+        let items = self
+            .news_items()
+            .into_iter()
+            .map(|(language, item)| {
+                let mut meta = vec![Span::styled(
+                    format!("{} · {}", item.date, item.source),
+                    Style::default().fg(Color::DarkGray),
+                )];
+
+                // the badge tells the language and the source of the news in the timeline
+                if is_timeline {
+                    meta.insert(
+                        0,
+                        Span::styled(
+                            format!("[{} {}] ", language.code(), language.source().name()),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                    );
+                }
+
+                ListItem::new(vec![
+                    Line::from(Span::styled(
+                        zh::convert(&item.title, script),
+                        Style::default().fg(Color::Blue),
+                    )),
+                    Line::from(meta),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let summary = match self.list_state.selected() {
+            Some(index) => match self.news_items().get(index) {
+                Some((_, item)) => {
+                    format!("{}\n\n{}", zh::convert(&item.summary, script), item.link)
+                }
+                _ => String::default(),
+            },
+            _ => String::default(),
+        };
+        frame.render_widget(
+            Paragraph::new(summary)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::ALL)),
+            detail_area,
+        );
+
+        let footer = if self.msg.is_empty() {
+            format!(
+                "q:{} j/k:{} r:{} l:{} t:{} o:{}",
+                tr("quit"),
+                tr("move"),
+                tr("refresh"),
+                tr("language"),
+                tr("timeline"),
+                tr("source-link")
+            )
+        } else {
+            self.msg.clone()
+        };
+        frame.render_widget(
+            Line::from(Span::styled(footer, Style::default().fg(Color::DarkGray))),
+            footer_area,
+        );
+    }
+}
@@ -1,4 +1,48 @@
-use chrono::{FixedOffset, TimeZone, Utc};
+use super::config::Network;
+use anyhow::Result;
+use chrono::{FixedOffset, NaiveDateTime, TimeZone, Utc};
+use egui::Context;
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    NoProxy, Proxy,
+};
+use std::time::{Duration, Instant};
+
+// How long the edits pause before they are saved
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+// Coalesce the saves of the edits until they pause, so a text field is not saved on every keystroke
+#[derive(Clone, Debug, Default)]
+pub struct Debounce {
+    changed_at: Option<Instant>,
+}
+
+impl Debounce {
+    pub fn touch(&mut self) {
+        self.changed_at = Some(Instant::now());
+    }
+
+    // True once the edits have paused, a repaint is requested for then, as the frames are only
+    // painted on demand
+    pub fn is_due(&mut self, ctx: &Context) -> bool {
+        match self.changed_at {
+            Some(at) if at.elapsed() >= DEBOUNCE_DELAY => {
+                self.changed_at = None;
+                true
+            }
+            Some(at) => {
+                ctx.request_repaint_after(DEBOUNCE_DELAY - at.elapsed());
+                false
+            }
+            None => false,
+        }
+    }
+
+    // True if the edits are not saved yet, e.g. when the panel is left or the app exits
+    pub fn flush(&mut self) -> bool {
+        self.changed_at.take().is_some()
+    }
+}
 
 pub fn timestamp() -> i64 {
     Utc::now().timestamp()
@@ -11,3 +55,35 @@ pub fn time_from_utc_seconds(sec: i64) -> String {
         .unwrap();
     format!("{}", time.format("%Y-%m-%d %H:%M"))
 }
+
+// The odaily dates are in China Standard Time, the CryptoCompare dates are formatted in UTC
+pub const ODAILY_UTC_OFFSET_SECS: i32 = 8 * 3600;
+
+// The timestamp of the date in the time zone of the UTC offset
+pub fn timestamp_at(date: &str, offset_secs: i32) -> Option<i64> {
+    FixedOffset::east_opt(offset_secs)?
+        .from_local_datetime(&parse_date(date)?)
+        .single()
+        .map(|time| time.timestamp())
+}
+
+// Parse the date of the news item, e.g. `2023-07-12 10:39` or `2023-07-12 10:39:06`
+pub fn parse_date(date: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(date, fmt).ok())
+}
+
+// The local servers, e.g. of the translation, are not reachable through most proxies
+const NO_PROXY_HOSTS: &str = "localhost,127.0.0.1,::1";
+
+// Client builder with the timeout and the proxy of the network settings
+pub fn http_client(conf: &Network) -> Result<ClientBuilder> {
+    let mut builder = Client::builder().timeout(Duration::from_secs(conf.timeout_secs));
+    if !conf.proxy.is_empty() {
+        let proxy = Proxy::all(&conf.proxy)?.no_proxy(NoProxy::from_string(NO_PROXY_HOSTS));
+        builder = builder.proxy(proxy);
+    }
+
+    Ok(builder)
+}
@@ -0,0 +1,208 @@
+use super::{
+    config::{Config, Webhook},
+    news::{self, NewsItem, Source},
+    store, util,
+    worker::Worker,
+};
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: u32 = 8;
+
+// The retry delay doubles after each failed attempt
+const RETRY_DELAY_SECS: i64 = 60;
+
+// How often the sender looks for the due retries while no news comes
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+pub const SIGNATURE_HEADER: &str = "X-Cpnews-Signature";
+
+// The deliveries and the retries run on the sender, which is the only user of the queue file
+static SENDER: Worker = Worker::new("webhook", RETRY_INTERVAL, retry_due);
+
+// The config of the latest delivery, which the retries go by
+static CONF: Mutex<Option<Config>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Payload {
+    pub source: String,
+    pub items: Vec<NewsItem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct QueueItem {
+    url: String,
+    body: String,
+    attempts: u32,
+    next_retry_at: i64,
+}
+
+fn signature(secret: &str, body: &str) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(body.as_bytes());
+
+    let hex = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+
+    Ok(format!("sha256={hex}"))
+}
+
+fn post(conf: &Config, webhook: &Webhook, body: &str) -> Result<()> {
+    let client = util::http_client(&conf.network)?.timeout(TIMEOUT).build()?;
+
+    let mut req = client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+
+    if !webhook.secret.is_empty() {
+        req = req.header(SIGNATURE_HEADER, signature(&webhook.secret, body)?);
+    }
+
+    let resp = req.send()?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("{} responded {}", webhook.url, resp.status()));
+    }
+
+    Ok(())
+}
+
+fn load_queue(path: &Path) -> Vec<QueueItem> {
+    store::load::<Vec<QueueItem>>(path).unwrap_or_default()
+}
+
+fn save_queue(path: &Path, queue: &Vec<QueueItem>) -> Result<()> {
+    match serde_json::to_string_pretty(queue) {
+        Ok(text) => store::write(path, text),
+        Err(e) => Err(anyhow!("{e:?}")),
+    }
+}
+
+fn push_failed(queue: &mut Vec<QueueItem>, url: &str, body: String, attempts: u32) {
+    if attempts >= MAX_ATTEMPTS {
+        log::warn!("drop webhook payload of {url} after {attempts} attempts");
+        return;
+    }
+
+    queue.push(QueueItem {
+        url: url.to_string(),
+        body,
+        attempts,
+        next_retry_at: util::timestamp() + (RETRY_DELAY_SECS << (attempts - 1)),
+    });
+}
+
+// Retry the due items of the queue, the items of the removed webhooks are dropped
+fn retry(conf: &Config, queue: Vec<QueueItem>) -> Vec<QueueItem> {
+    let now = util::timestamp();
+    let mut failed = vec![];
+
+    for item in queue.into_iter() {
+        let webhook = match conf.webhooks.iter().find(|w| w.url == item.url) {
+            Some(webhook) => webhook,
+            _ => continue,
+        };
+
+        if item.next_retry_at > now {
+            failed.push(item);
+            continue;
+        }
+
+        if let Err(e) = post(conf, webhook, &item.body) {
+            log::warn!("{e:?}");
+            push_failed(&mut failed, &item.url, item.body, item.attempts + 1);
+        }
+    }
+
+    failed
+}
+
+fn queue_path(conf: &Config) -> PathBuf {
+    conf.data_dir.join("webhook-queue.json")
+}
+
+fn retry_due() {
+    let conf = match CONF.lock().unwrap().clone() {
+        Some(conf) => conf,
+        _ => return,
+    };
+
+    let path = queue_path(&conf);
+    let queue = load_queue(&path);
+    if queue.is_empty() {
+        return;
+    }
+
+    if let Err(e) = save_queue(&path, &retry(&conf, queue)) {
+        log::warn!("{e:?}");
+    }
+}
+
+// Post the new items to the webhooks on the sender, so a slow webhook does not hold up the news.
+// The failed deliveries are persisted and retried later
+pub fn deliver(conf: &Config, source: Source, items: &[NewsItem]) {
+    if conf.webhooks.is_empty() {
+        return;
+    }
+
+    *CONF.lock().unwrap() = Some(conf.clone());
+
+    let conf = conf.clone();
+    let items = items.to_vec();
+    SENDER.run(move || send(&conf, source, &items));
+}
+
+// Wait for the deliveries, e.g. before a command line process exits
+pub fn wait() {
+    SENDER.wait();
+}
+
+fn send(conf: &Config, source: Source, items: &[NewsItem]) {
+    let path = queue_path(conf);
+    let mut queue = retry(conf, load_queue(&path));
+
+    for webhook in conf.webhooks.iter() {
+        let items = items
+            .iter()
+            .filter(|item| webhook.query.is_empty() || news::matches(item, &webhook.query))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            continue;
+        }
+
+        let payload = Payload {
+            source: source.name().to_string(),
+            items,
+        };
+
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                log::warn!("{e:?}");
+                continue;
+            }
+        };
+
+        if let Err(e) = post(conf, webhook, &body) {
+            log::warn!("{e:?}");
+            push_failed(&mut queue, &webhook.url, body, 1);
+        }
+    }
+
+    if let Err(e) = save_queue(&path, &queue) {
+        log::warn!("{e:?}");
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError, SendError, Sender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// Counts the job as done once it is dropped, also if it panics or is never run
+struct Pending(&'static AtomicUsize);
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// A background thread running the jobs one by one, so the slow jobs, e.g. the webhooks and the
+// hooks, do not hold up the fetching of the news. The thread is started by the first job, and
+// `on_idle` is run whenever no job has come for `idle_interval`
+pub struct Worker {
+    name: &'static str,
+    idle_interval: Duration,
+    on_idle: fn(),
+    tx: Mutex<Option<Sender<Job>>>,
+    pending: AtomicUsize,
+}
+
+impl Worker {
+    pub const fn new(name: &'static str, idle_interval: Duration, on_idle: fn()) -> Self {
+        Self {
+            name,
+            idle_interval,
+            on_idle,
+            tx: Mutex::new(None),
+            pending: AtomicUsize::new(0),
+        }
+    }
+
+    fn start(&'static self) -> Sender<Job> {
+        let (tx, rx) = mpsc::channel::<Job>();
+
+        let spawned = thread::Builder::new()
+            .name(self.name.to_string())
+            .spawn(move || loop {
+                match rx.recv_timeout(self.idle_interval) {
+                    // a panicking job does not take the jobs after it down with the thread
+                    Ok(job) => {
+                        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                            log::warn!("{}: a job panicked", self.name);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => (self.on_idle)(),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            });
+
+        if let Err(e) = spawned {
+            log::warn!("{}: {e:?}", self.name);
+        }
+
+        tx
+    }
+
+    pub fn run(&'static self, job: impl FnOnce() + Send + 'static) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let pending = Pending(&self.pending);
+        let mut job: Job = Box::new(move || {
+            let _pending = pending;
+            job();
+        });
+
+        let mut tx = self.tx.lock().unwrap();
+        for _ in 0..2 {
+            match tx.get_or_insert_with(|| self.start()).send(job) {
+                Ok(_) => return,
+
+                // the thread is gone, e.g. by a panicking job, so it is started again
+                Err(SendError(unsent)) => {
+                    job = unsent;
+                    *tx = None;
+                }
+            }
+        }
+
+        log::warn!("{} is not running", self.name);
+    }
+
+    // Wait for the jobs sent so far, e.g. before a command line process exits
+    pub fn wait(&self) {
+        while self.pending.load(Ordering::SeqCst) > 0 {
+            thread::sleep(WAIT_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+use zhconv::{get_builtin_converter, Variant};
+
+// The converted texts are cached, so that the visible items are not converted on every frame
+const MAX_CACHE_ITEM: usize = 2000;
+
+static CACHE: OnceLock<Mutex<HashMap<(ChineseScript, String), String>>> = OnceLock::new();
+
+// Script of the displayed Chinese news, the odaily news is in Simplified Chinese
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ChineseScript {
+    #[default]
+    Simplified,
+    Taiwan,
+    HongKong,
+}
+
+impl ChineseScript {
+    pub const ALL: [ChineseScript; 3] = [
+        ChineseScript::Simplified,
+        ChineseScript::Taiwan,
+        ChineseScript::HongKong,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChineseScript::Simplified => "简体中文",
+            ChineseScript::Taiwan => "繁體中文(台灣)",
+            ChineseScript::HongKong => "繁體中文(香港)",
+        }
+    }
+
+    fn variant(&self) -> Variant {
+        match self {
+            ChineseScript::Simplified => Variant::ZhHans,
+            ChineseScript::Taiwan => Variant::ZhTW,
+            ChineseScript::HongKong => Variant::ZhHK,
+        }
+    }
+}
+
+fn has_chinese(text: &str) -> bool {
+    text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c))
+}
+
+// Convert the text for display, the text without Chinese is returned as is
+pub fn convert(text: &str, script: ChineseScript) -> String {
+    if script == ChineseScript::Simplified || !has_chinese(text) {
+        return text.to_string();
+    }
+
+    let cache = CACHE.get_or_init(Default::default);
+    let key = (script, text.to_string());
+    if let Some(converted) = cache.lock().unwrap().get(&key) {
+        return converted.clone();
+    }
+
+    let converted = get_builtin_converter(script.variant()).convert(text);
+
+    let mut cache = cache.lock().unwrap();
+    if cache.len() >= MAX_CACHE_ITEM {
+        cache.clear();
+    }
+    cache.insert(key, converted.clone());
+    converted
+}
+
+// Convert the text to Simplified Chinese, so that both scripts can be compared
+pub fn to_simplified(text: &str) -> String {
+    if !has_chinese(text) {
+        return text.to_string();
+    }
+
+    get_builtin_converter(Variant::ZhHans).convert(text)
+}